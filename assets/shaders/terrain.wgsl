@@ -0,0 +1,85 @@
+#import bevy_pbr::mesh_view_bindings
+#import bevy_pbr::mesh_bindings
+
+#import bevy_pbr::pbr_types
+#import bevy_pbr::utils
+#import bevy_pbr::clustered_forward
+#import bevy_pbr::lighting
+#import bevy_pbr::shadows
+#import bevy_pbr::pbr_functions
+
+@group(1) @binding(0)
+var atlas_texture: texture_2d<f32>;
+@group(1) @binding(1)
+var atlas_sampler: sampler;
+@group(1) @binding(2)
+var<uniform> sections: u32;
+@group(1) @binding(3)
+var normal_texture: texture_2d<f32>;
+@group(1) @binding(4)
+var metallic_roughness_texture: texture_2d<f32>;
+@group(1) @binding(5)
+var occlusion_texture: texture_2d<f32>;
+
+struct FragmentInput {
+    @builtin(front_facing) is_front: bool,
+    @builtin(position) frag_coord: vec4<f32>,
+    #import bevy_pbr::mesh_vertex_output
+};
+
+// Samples one section of an atlas, which repeats every uv unit.
+// The gradients of the continuous uv are used, so that the mip level does not jump at the repetition borders.
+fn sample_section(texture: texture_2d<f32>, section: u32, uv: vec2<f32>, ddx: vec2<f32>, ddy: vec2<f32>) -> vec4<f32> {
+    let width = 1.0 / f32(sections);
+    let section_uv = vec2<f32>((f32(section) + fract(uv.x)) * width, fract(uv.y));
+    let scale = vec2<f32>(width, 1.0);
+    return textureSampleGrad(texture, atlas_sampler, section_uv, ddx * scale, ddy * scale);
+}
+
+// The vertex color holds the weights of the first four sections, the fifth gets the rest.
+fn blend(texture: texture_2d<f32>, weights: vec4<f32>, uv: vec2<f32>, ddx: vec2<f32>, ddy: vec2<f32>) -> vec4<f32> {
+    let rest = max(1.0 - weights.x - weights.y - weights.z - weights.w, 0.0);
+    return sample_section(texture, 0u, uv, ddx, ddy) * weights.x
+        + sample_section(texture, 1u, uv, ddx, ddy) * weights.y
+        + sample_section(texture, 2u, uv, ddx, ddy) * weights.z
+        + sample_section(texture, 3u, uv, ddx, ddy) * weights.w
+        + sample_section(texture, 4u, uv, ddx, ddy) * rest;
+}
+
+@fragment
+fn fragment(in: FragmentInput) -> @location(0) vec4<f32> {
+    let ddx = dpdx(in.uv);
+    let ddy = dpdy(in.uv);
+#ifdef VERTEX_COLORS
+    let weights = in.color;
+#else
+    let weights = vec4<f32>(1.0, 0.0, 0.0, 0.0);
+#endif
+    let color = blend(atlas_texture, weights, in.uv, ddx, ddy);
+    let metallic_roughness = blend(metallic_roughness_texture, weights, in.uv, ddx, ddy);
+
+    var pbr_input: PbrInput = pbr_input_new();
+    pbr_input.material.base_color = vec4<f32>(color.rgb, 1.0);
+    // same channels as the StandardMaterial: green is the roughness, blue the metallic
+    pbr_input.material.perceptual_roughness = pbr_input.material.perceptual_roughness * metallic_roughness.g;
+    pbr_input.material.metallic = pbr_input.material.metallic * metallic_roughness.b;
+    pbr_input.occlusion = blend(occlusion_texture, weights, in.uv, ddx, ddy).r;
+    pbr_input.frag_coord = in.frag_coord;
+    pbr_input.world_position = in.world_position;
+    pbr_input.world_normal = in.world_normal;
+    pbr_input.is_orthographic = view.projection[3].w == 1.0;
+    // the terrain is only seen from above, the atlas normal is applied with its own tangent frame below
+    pbr_input.N = prepare_world_normal(in.world_normal, false, in.is_front);
+    // u runs along the x axis of the world and v along the z axis, so the tangent frame follows from the normal
+    let tangent = normalize(vec3<f32>(1.0, 0.0, 0.0) - pbr_input.N * pbr_input.N.x);
+    let bitangent = cross(pbr_input.N, tangent);
+    let normal = blend(normal_texture, weights, in.uv, ddx, ddy).rgb * 2.0 - 1.0;
+    pbr_input.N = normalize(normal.x * tangent + normal.y * bitangent + normal.z * pbr_input.N);
+    pbr_input.V = calculate_view(in.world_position, pbr_input.is_orthographic);
+
+    var output_color = pbr(pbr_input);
+#ifdef TONEMAP_IN_SHADER
+    output_color = tone_mapping(output_color);
+#endif
+    return output_color;
+}
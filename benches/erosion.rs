@@ -0,0 +1,40 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+// the game is a binary, so the modules are compiled into the benchmark directly
+#[allow(dead_code)]
+#[path = "../src/world/erosion.rs"]
+mod erosion;
+#[allow(dead_code)]
+#[path = "../src/world/noise.rs"]
+mod noise;
+
+use self::{
+    erosion::{erode, ErosionConfig},
+    noise::{default_layers, WorldNoise},
+};
+
+/// Erodes the heights of the default world, 430 x 430 samples one meter apart, with the default erosion config.
+fn default_world(c: &mut Criterion) {
+    let (size, scale, seed) = (430, 1.0, 0);
+    let noise = WorldNoise::new(seed, &default_layers());
+    let heights: Vec<f32> = (0..size)
+        .flat_map(|a| (0..size).map(move |b| (a, b)))
+        .map(|(a, b)| noise.get_height(a as f32 * scale, b as f32 * scale))
+        .collect();
+    let config = ErosionConfig::default();
+    c.bench_function("erode default world", |b| {
+        b.iter_batched(
+            || heights.clone(),
+            |mut heights| erode(&mut heights, size, scale, seed, &config),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group! {
+    name = benches;
+    // a whole erosion per sample, the default 100 samples would take too long
+    config = Criterion::default().sample_size(10);
+    targets = default_world
+}
+criterion_main!(benches);
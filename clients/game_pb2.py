@@ -2,10 +2,10 @@
 # Generated by the protocol buffer compiler.  DO NOT EDIT!
 # source: game.proto
 """Generated protocol buffer code."""
-from google.protobuf.internal import builder as _builder
 from google.protobuf import descriptor as _descriptor
 from google.protobuf import descriptor_pool as _descriptor_pool
 from google.protobuf import symbol_database as _symbol_database
+from google.protobuf.internal import builder as _builder
 # @@protoc_insertion_point(imports)
 
 _sym_db = _symbol_database.Default()
@@ -13,23 +13,63 @@ _sym_db = _symbol_database.Default()
 
 
 
-DESCRIPTOR = _descriptor_pool.Default().AddSerializedFile(b'\n\ngame.proto\x12\x04game\"\x07\n\x05\x45mpty\"$\n\x0cInputRequest\x12\t\n\x01x\x18\x01 \x01(\x02\x12\t\n\x01z\x18\x02 \x01(\x02\"u\n\nPlayerView\x12\"\n\x0bsurrounding\x18\x01 \x03(\x0b\x32\r.game.Terrain\x12\t\n\x01x\x18\x02 \x01(\x02\x12\t\n\x01y\x18\x03 \x01(\x02\x12\t\n\x01z\x18\x04 \x01(\x02\x12\x10\n\x08\x64istance\x18\x05 \x01(\x02\x12\x10\n\x08\x66inished\x18\x06 \x01(\x08\"\'\n\x07Terrain\x12\x0e\n\x06height\x18\x01 \x01(\x02\x12\x0c\n\x04kind\x18\x02 \x01(\x05\"\'\n\x05Score\x12\x0f\n\x07timings\x18\x01 \x03(\x03\x12\r\n\x05total\x18\x02 \x01(\x05\x32\xce\x01\n\x0bMainService\x12\"\n\x06health\x12\x0b.game.Empty\x1a\x0b.game.Empty\x12)\n\x08getState\x12\x0b.game.Empty\x1a\x10.game.PlayerView\x12(\n\x05input\x12\x12.game.InputRequest\x1a\x0b.game.Empty\x12 \n\x04kill\x12\x0b.game.Empty\x1a\x0b.game.Empty\x12$\n\x08getScore\x12\x0b.game.Empty\x1a\x0b.game.Scoreb\x06proto3')
-
-_builder.BuildMessageAndEnumDescriptors(DESCRIPTOR, globals())
-_builder.BuildTopDescriptorsAndMessages(DESCRIPTOR, 'game_pb2', globals())
-if _descriptor._USE_C_DESCRIPTORS == False:
+DESCRIPTOR = _descriptor_pool.Default().AddSerializedFile(b'\n\ngame.proto\x12\x04game\"\x07\n\x05\x45mpty\"I\n\x0cInputRequest\x12\t\n\x01x\x18\x01 \x01(\x02\x12\t\n\x01z\x18\x02 \x01(\x02\x12\x0e\n\x06player\x18\x03 \x01(\x05\x12\x13\n\x0b\x65nvironment\x18\x04 \x01(\x05\"[\n\x0cStateRequest\x12\x0e\n\x06player\x18\x01 \x01(\x05\x12\x13\n\x0b\x65nvironment\x18\x02 \x01(\x05\x12&\n\x0bobservation\x18\x03 \x01(\x0b\x32\x11.game.Observation\"\x84\x01\n\x0bObservation\x12\x13\n\x06radius\x18\x01 \x01(\x05H\x00\x88\x01\x01\x12\x14\n\x07spacing\x18\x02 \x01(\x02H\x01\x88\x01\x01\x12\x17\n\x0fheading_aligned\x18\x03 \x01(\x08\x12\x1a\n\x05lidar\x18\x04 \x01(\x0b\x32\x0b.game.LidarB\t\n\x07_radiusB\n\n\x08_spacing\"@\n\x05Lidar\x12\x0c\n\x04rays\x18\x01 \x01(\x05\x12\x0b\n\x03\x66ov\x18\x02 \x01(\x02\x12\r\n\x05range\x18\x03 \x01(\x02\x12\r\n\x05pitch\x18\x04 \x01(\x02\"*\n\x08LidarHit\x12\x10\n\x08\x64istance\x18\x01 \x01(\x02\x12\x0c\n\x04kind\x18\x02 \x01(\x05\")\n\x12\x45nvironmentRequest\x12\x13\n\x0b\x65nvironment\x18\x01 \x01(\x05\"?\n\x0cResetRequest\x12\x11\n\x04seed\x18\x01 \x01(\rH\x00\x88\x01\x01\x12\x13\n\x0b\x65nvironment\x18\x02 \x01(\x05\x42\x07\n\x05_seed\"\xed\x02\n\nPlayerView\x12\"\n\x0bsurrounding\x18\x01 \x03(\x0b\x32\r.game.Terrain\x12\t\n\x01x\x18\x02 \x01(\x02\x12\t\n\x01y\x18\x03 \x01(\x02\x12\t\n\x01z\x18\x04 \x01(\x02\x12\x10\n\x08\x64istance\x18\x05 \x01(\x02\x12\x10\n\x08\x66inished\x18\x06 \x01(\x08\x12\x1e\n\x08velocity\x18\x07 \x01(\x0b\x32\x0c.game.Vector\x12&\n\x10\x61ngular_velocity\x18\x08 \x01(\x0b\x32\x0c.game.Vector\x12 \n\ncheckpoint\x18\t \x01(\x0b\x32\x0c.game.Vector\x12\x18\n\x10\x63heckpoint_index\x18\n \x01(\x05\x12\r\n\x05\x66rame\x18\x0b \x01(\x03\x12\x1d\n\x05lidar\x18\x0c \x03(\x0b\x32\x0e.game.LidarHit\x12\x0e\n\x06reward\x18\r \x01(\x02\x12\x0c\n\x04\x64one\x18\x0e \x01(\x08\x12&\n\x0btermination\x18\x0f \x01(\x0e\x32\x11.game.Termination\"J\n\nStepResult\x12\x1e\n\x04view\x18\x01 \x01(\x0b\x32\x10.game.PlayerView\x12\x0e\n\x06reward\x18\x02 \x01(\x02\x12\x0c\n\x04\x64one\x18\x03 \x01(\x08\"6\n\x10\x42\x61tchStepRequest\x12\"\n\x06inputs\x18\x01 \x03(\x0b\x32\x12.game.InputRequest\"4\n\x0f\x42\x61tchStepResult\x12!\n\x07results\x18\x01 \x03(\x0b\x32\x10.game.StepResult\")\n\x06Vector\x12\t\n\x01x\x18\x01 \x01(\x02\x12\t\n\x01y\x18\x02 \x01(\x02\x12\t\n\x01z\x18\x03 \x01(\x02\"T\n\x07Terrain\x12\x0e\n\x06height\x18\x01 \x01(\x02\x12\x0c\n\x04kind\x18\x02 \x01(\x05\x12\r\n\x05slope\x18\x03 \x01(\x02\x12\x1c\n\x06normal\x18\x04 \x01(\x0b\x32\x0c.game.Vector\"%\n\x06Scores\x12\x1b\n\x06scores\x18\x01 \x03(\x0b\x32\x0b.game.Score\"\xa3\x01\n\x05Score\x12\x0f\n\x07timings\x18\x01 \x03(\x03\x12\r\n\x05total\x18\x02 \x01(\x05\x12\x0c\n\x04name\x18\x03 \x01(\t\x12\x15\n\x08position\x18\x04 \x01(\x05H\x00\x88\x01\x01\x12\x0e\n\x06\x66rames\x18\x05 \x01(\x03\x12\x10\n\x08\x66inished\x18\x06 \x01(\x08\x12&\n\x0btermination\x18\x07 \x01(\x0e\x32\x11.game.TerminationB\x0b\n\t_position\"d\n\x06\x43onfig\x12\'\n\x0c\x63ontrol_mode\x18\x01 \x01(\x0e\x32\x11.game.ControlMode\x12\x11\n\tmax_speed\x18\x02 \x01(\x02\x12\x1e\n\x07physics\x18\x03 \x01(\x0b\x32\r.game.Physics\"\xf1\x01\n\x07Physics\x12\x10\n\x08timestep\x18\x01 \x01(\x02\x12\x10\n\x08substeps\x18\x02 \x01(\x05\x12\x0f\n\x07gravity\x18\x03 \x01(\x02\x12\x0c\n\x04mass\x18\x04 \x01(\x02\x12\x10\n\x08\x66riction\x18\x05 \x01(\x02\x12\x13\n\x0brestitution\x18\x06 \x01(\x02\x12\x16\n\x0elinear_damping\x18\x07 \x01(\x02\x12\x17\n\x0f\x61ngular_damping\x18\x08 \x01(\x02\x12\x19\n\x0cmax_velocity\x18\t \x01(\x02H\x00\x88\x01\x01\x12\x1f\n\x08surfaces\x18\n \x03(\x0b\x32\r.game.SurfaceB\x0f\n\r_max_velocity\"k\n\x07Surface\x12\x0c\n\x04kind\x18\x01 \x01(\x05\x12\x10\n\x08\x66riction\x18\x02 \x01(\x02\x12\x1a\n\x12rolling_resistance\x18\x03 \x01(\x02\x12\x16\n\tmax_speed\x18\x04 \x01(\x02H\x00\x88\x01\x01\x42\x0c\n\n_max_speed\"=\n\nMapRequest\x12\x13\n\x0b\x65nvironment\x18\x01 \x01(\x05\x12\x11\n\x04step\x18\x02 \x01(\x05H\x00\x88\x01\x01\x42\x07\n\x05_step\"b\n\x03Map\x12\x0c\n\x04size\x18\x01 \x01(\x05\x12\x0f\n\x07spacing\x18\x02 \x01(\x02\x12\x1c\n\x06origin\x18\x03 \x01(\x0b\x32\x0c.game.Vector\x12\x1e\n\x07samples\x18\x04 \x03(\x0b\x32\r.game.Terrain\".\n\x05Track\x12%\n\x0b\x63heckpoints\x18\x01 \x03(\x0b\x32\x10.game.Checkpoint\"<\n\nCheckpoint\x12\x1e\n\x08position\x18\x01 \x01(\x0b\x32\x0c.game.Vector\x12\x0e\n\x06radius\x18\x02 \x01(\x02*_\n\x0bTermination\x12\x0b\n\x07RUNNING\x10\x00\x12\x0c\n\x08\x46INISHED\x10\x01\x12\x0e\n\nMAX_FRAMES\x10\x02\x12\x10\n\x0cLEFT_TERRAIN\x10\x03\x12\x08\n\x04\x46\x45LL\x10\x04\x12\t\n\x05STUCK\x10\x05*D\n\x0b\x43ontrolMode\x12\x0c\n\x08VELOCITY\x10\x00\x12\t\n\x05\x46ORCE\x10\x01\x12\n\n\x06TORQUE\x10\x02\x12\x10\n\x0c\x41\x43\x43\x45LERATION\x10\x03\x32\xae\x04\n\x0bMainService\x12\"\n\x06health\x12\x0b.game.Empty\x1a\x0b.game.Empty\x12\x30\n\x08getState\x12\x12.game.StateRequest\x1a\x10.game.PlayerView\x12(\n\x05input\x12\x12.game.InputRequest\x1a\x0b.game.Empty\x12 \n\x04kill\x12\x0b.game.Empty\x1a\x0b.game.Empty\x12\x32\n\x08getScore\x12\x18.game.EnvironmentRequest\x1a\x0c.game.Scores\x12(\n\x05reset\x12\x12.game.ResetRequest\x1a\x0b.game.Empty\x12,\n\x04step\x12\x12.game.InputRequest\x1a\x10.game.StepResult\x12:\n\tbatchStep\x12\x16.game.BatchStepRequest\x1a\x15.game.BatchStepResult\x12\x33\n\x07session\x12\x12.game.InputRequest\x1a\x10.game.PlayerView(\x01\x30\x01\x12&\n\tgetConfig\x12\x0b.game.Empty\x1a\x0c.game.Config\x12%\n\x06getMap\x12\x10.game.MapRequest\x1a\t.game.Map\x12\x31\n\x08getTrack\x12\x18.game.EnvironmentRequest\x1a\x0b.game.Trackb\x06proto3')
 
-  DESCRIPTOR._options = None
-  _EMPTY._serialized_start=20
-  _EMPTY._serialized_end=27
-  _INPUTREQUEST._serialized_start=29
-  _INPUTREQUEST._serialized_end=65
-  _PLAYERVIEW._serialized_start=67
-  _PLAYERVIEW._serialized_end=184
-  _TERRAIN._serialized_start=186
-  _TERRAIN._serialized_end=225
-  _SCORE._serialized_start=227
-  _SCORE._serialized_end=266
-  _MAINSERVICE._serialized_start=269
-  _MAINSERVICE._serialized_end=475
+_globals = globals()
+_builder.BuildMessageAndEnumDescriptors(DESCRIPTOR, _globals)
+_builder.BuildTopDescriptorsAndMessages(DESCRIPTOR, 'game_pb2', _globals)
+if not _descriptor._USE_C_DESCRIPTORS:
+  DESCRIPTOR._loaded_options = None
+  _globals['_TERMINATION']._serialized_start=2166
+  _globals['_TERMINATION']._serialized_end=2261
+  _globals['_CONTROLMODE']._serialized_start=2263
+  _globals['_CONTROLMODE']._serialized_end=2331
+  _globals['_EMPTY']._serialized_start=20
+  _globals['_EMPTY']._serialized_end=27
+  _globals['_INPUTREQUEST']._serialized_start=29
+  _globals['_INPUTREQUEST']._serialized_end=102
+  _globals['_STATEREQUEST']._serialized_start=104
+  _globals['_STATEREQUEST']._serialized_end=195
+  _globals['_OBSERVATION']._serialized_start=198
+  _globals['_OBSERVATION']._serialized_end=330
+  _globals['_LIDAR']._serialized_start=332
+  _globals['_LIDAR']._serialized_end=396
+  _globals['_LIDARHIT']._serialized_start=398
+  _globals['_LIDARHIT']._serialized_end=440
+  _globals['_ENVIRONMENTREQUEST']._serialized_start=442
+  _globals['_ENVIRONMENTREQUEST']._serialized_end=483
+  _globals['_RESETREQUEST']._serialized_start=485
+  _globals['_RESETREQUEST']._serialized_end=548
+  _globals['_PLAYERVIEW']._serialized_start=551
+  _globals['_PLAYERVIEW']._serialized_end=916
+  _globals['_STEPRESULT']._serialized_start=918
+  _globals['_STEPRESULT']._serialized_end=992
+  _globals['_BATCHSTEPREQUEST']._serialized_start=994
+  _globals['_BATCHSTEPREQUEST']._serialized_end=1048
+  _globals['_BATCHSTEPRESULT']._serialized_start=1050
+  _globals['_BATCHSTEPRESULT']._serialized_end=1102
+  _globals['_VECTOR']._serialized_start=1104
+  _globals['_VECTOR']._serialized_end=1145
+  _globals['_TERRAIN']._serialized_start=1147
+  _globals['_TERRAIN']._serialized_end=1231
+  _globals['_SCORES']._serialized_start=1233
+  _globals['_SCORES']._serialized_end=1270
+  _globals['_SCORE']._serialized_start=1273
+  _globals['_SCORE']._serialized_end=1436
+  _globals['_CONFIG']._serialized_start=1438
+  _globals['_CONFIG']._serialized_end=1538
+  _globals['_PHYSICS']._serialized_start=1541
+  _globals['_PHYSICS']._serialized_end=1782
+  _globals['_SURFACE']._serialized_start=1784
+  _globals['_SURFACE']._serialized_end=1891
+  _globals['_MAPREQUEST']._serialized_start=1893
+  _globals['_MAPREQUEST']._serialized_end=1954
+  _globals['_MAP']._serialized_start=1956
+  _globals['_MAP']._serialized_end=2054
+  _globals['_TRACK']._serialized_start=2056
+  _globals['_TRACK']._serialized_end=2102
+  _globals['_CHECKPOINT']._serialized_start=2104
+  _globals['_CHECKPOINT']._serialized_end=2164
+  _globals['_MAINSERVICE']._serialized_start=2334
+  _globals['_MAINSERVICE']._serialized_end=2892
 # @@protoc_insertion_point(module_scope)
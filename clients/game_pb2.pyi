@@ -1,50 +1,288 @@
 from google.protobuf.internal import containers as _containers
+from google.protobuf.internal import enum_type_wrapper as _enum_type_wrapper
 from google.protobuf import descriptor as _descriptor
 from google.protobuf import message as _message
-from typing import ClassVar as _ClassVar, Iterable as _Iterable, Mapping as _Mapping, Optional as _Optional, Union as _Union
+from collections.abc import Iterable as _Iterable, Mapping as _Mapping
+from typing import ClassVar as _ClassVar, Optional as _Optional, Union as _Union
 
 DESCRIPTOR: _descriptor.FileDescriptor
 
+class Termination(int, metaclass=_enum_type_wrapper.EnumTypeWrapper):
+    __slots__ = ()
+    RUNNING: _ClassVar[Termination]
+    FINISHED: _ClassVar[Termination]
+    MAX_FRAMES: _ClassVar[Termination]
+    LEFT_TERRAIN: _ClassVar[Termination]
+    FELL: _ClassVar[Termination]
+    STUCK: _ClassVar[Termination]
+
+class ControlMode(int, metaclass=_enum_type_wrapper.EnumTypeWrapper):
+    __slots__ = ()
+    VELOCITY: _ClassVar[ControlMode]
+    FORCE: _ClassVar[ControlMode]
+    TORQUE: _ClassVar[ControlMode]
+    ACCELERATION: _ClassVar[ControlMode]
+RUNNING: Termination
+FINISHED: Termination
+MAX_FRAMES: Termination
+LEFT_TERRAIN: Termination
+FELL: Termination
+STUCK: Termination
+VELOCITY: ControlMode
+FORCE: ControlMode
+TORQUE: ControlMode
+ACCELERATION: ControlMode
+
 class Empty(_message.Message):
-    __slots__ = []
+    __slots__ = ()
     def __init__(self) -> None: ...
 
 class InputRequest(_message.Message):
-    __slots__ = ["x", "z"]
+    __slots__ = ("x", "z", "player", "environment")
     X_FIELD_NUMBER: _ClassVar[int]
     Z_FIELD_NUMBER: _ClassVar[int]
+    PLAYER_FIELD_NUMBER: _ClassVar[int]
+    ENVIRONMENT_FIELD_NUMBER: _ClassVar[int]
     x: float
     z: float
-    def __init__(self, x: _Optional[float] = ..., z: _Optional[float] = ...) -> None: ...
+    player: int
+    environment: int
+    def __init__(self, x: _Optional[float] = ..., z: _Optional[float] = ..., player: _Optional[int] = ..., environment: _Optional[int] = ...) -> None: ...
 
-class PlayerView(_message.Message):
-    __slots__ = ["distance", "finished", "surrounding", "x", "y", "z"]
+class StateRequest(_message.Message):
+    __slots__ = ("player", "environment", "observation")
+    PLAYER_FIELD_NUMBER: _ClassVar[int]
+    ENVIRONMENT_FIELD_NUMBER: _ClassVar[int]
+    OBSERVATION_FIELD_NUMBER: _ClassVar[int]
+    player: int
+    environment: int
+    observation: Observation
+    def __init__(self, player: _Optional[int] = ..., environment: _Optional[int] = ..., observation: _Optional[_Union[Observation, _Mapping]] = ...) -> None: ...
+
+class Observation(_message.Message):
+    __slots__ = ("radius", "spacing", "heading_aligned", "lidar")
+    RADIUS_FIELD_NUMBER: _ClassVar[int]
+    SPACING_FIELD_NUMBER: _ClassVar[int]
+    HEADING_ALIGNED_FIELD_NUMBER: _ClassVar[int]
+    LIDAR_FIELD_NUMBER: _ClassVar[int]
+    radius: int
+    spacing: float
+    heading_aligned: bool
+    lidar: Lidar
+    def __init__(self, radius: _Optional[int] = ..., spacing: _Optional[float] = ..., heading_aligned: _Optional[bool] = ..., lidar: _Optional[_Union[Lidar, _Mapping]] = ...) -> None: ...
+
+class Lidar(_message.Message):
+    __slots__ = ("rays", "fov", "range", "pitch")
+    RAYS_FIELD_NUMBER: _ClassVar[int]
+    FOV_FIELD_NUMBER: _ClassVar[int]
+    RANGE_FIELD_NUMBER: _ClassVar[int]
+    PITCH_FIELD_NUMBER: _ClassVar[int]
+    rays: int
+    fov: float
+    range: float
+    pitch: float
+    def __init__(self, rays: _Optional[int] = ..., fov: _Optional[float] = ..., range: _Optional[float] = ..., pitch: _Optional[float] = ...) -> None: ...
+
+class LidarHit(_message.Message):
+    __slots__ = ("distance", "kind")
     DISTANCE_FIELD_NUMBER: _ClassVar[int]
-    FINISHED_FIELD_NUMBER: _ClassVar[int]
+    KIND_FIELD_NUMBER: _ClassVar[int]
+    distance: float
+    kind: int
+    def __init__(self, distance: _Optional[float] = ..., kind: _Optional[int] = ...) -> None: ...
+
+class EnvironmentRequest(_message.Message):
+    __slots__ = ("environment",)
+    ENVIRONMENT_FIELD_NUMBER: _ClassVar[int]
+    environment: int
+    def __init__(self, environment: _Optional[int] = ...) -> None: ...
+
+class ResetRequest(_message.Message):
+    __slots__ = ("seed", "environment")
+    SEED_FIELD_NUMBER: _ClassVar[int]
+    ENVIRONMENT_FIELD_NUMBER: _ClassVar[int]
+    seed: int
+    environment: int
+    def __init__(self, seed: _Optional[int] = ..., environment: _Optional[int] = ...) -> None: ...
+
+class PlayerView(_message.Message):
+    __slots__ = ("surrounding", "x", "y", "z", "distance", "finished", "velocity", "angular_velocity", "checkpoint", "checkpoint_index", "frame", "lidar", "reward", "done", "termination")
     SURROUNDING_FIELD_NUMBER: _ClassVar[int]
     X_FIELD_NUMBER: _ClassVar[int]
     Y_FIELD_NUMBER: _ClassVar[int]
     Z_FIELD_NUMBER: _ClassVar[int]
+    DISTANCE_FIELD_NUMBER: _ClassVar[int]
+    FINISHED_FIELD_NUMBER: _ClassVar[int]
+    VELOCITY_FIELD_NUMBER: _ClassVar[int]
+    ANGULAR_VELOCITY_FIELD_NUMBER: _ClassVar[int]
+    CHECKPOINT_FIELD_NUMBER: _ClassVar[int]
+    CHECKPOINT_INDEX_FIELD_NUMBER: _ClassVar[int]
+    FRAME_FIELD_NUMBER: _ClassVar[int]
+    LIDAR_FIELD_NUMBER: _ClassVar[int]
+    REWARD_FIELD_NUMBER: _ClassVar[int]
+    DONE_FIELD_NUMBER: _ClassVar[int]
+    TERMINATION_FIELD_NUMBER: _ClassVar[int]
+    surrounding: _containers.RepeatedCompositeFieldContainer[Terrain]
+    x: float
+    y: float
+    z: float
     distance: float
     finished: bool
-    surrounding: _containers.RepeatedCompositeFieldContainer[Terrain]
+    velocity: Vector
+    angular_velocity: Vector
+    checkpoint: Vector
+    checkpoint_index: int
+    frame: int
+    lidar: _containers.RepeatedCompositeFieldContainer[LidarHit]
+    reward: float
+    done: bool
+    termination: Termination
+    def __init__(self, surrounding: _Optional[_Iterable[_Union[Terrain, _Mapping]]] = ..., x: _Optional[float] = ..., y: _Optional[float] = ..., z: _Optional[float] = ..., distance: _Optional[float] = ..., finished: _Optional[bool] = ..., velocity: _Optional[_Union[Vector, _Mapping]] = ..., angular_velocity: _Optional[_Union[Vector, _Mapping]] = ..., checkpoint: _Optional[_Union[Vector, _Mapping]] = ..., checkpoint_index: _Optional[int] = ..., frame: _Optional[int] = ..., lidar: _Optional[_Iterable[_Union[LidarHit, _Mapping]]] = ..., reward: _Optional[float] = ..., done: _Optional[bool] = ..., termination: _Optional[_Union[Termination, str]] = ...) -> None: ...
+
+class StepResult(_message.Message):
+    __slots__ = ("view", "reward", "done")
+    VIEW_FIELD_NUMBER: _ClassVar[int]
+    REWARD_FIELD_NUMBER: _ClassVar[int]
+    DONE_FIELD_NUMBER: _ClassVar[int]
+    view: PlayerView
+    reward: float
+    done: bool
+    def __init__(self, view: _Optional[_Union[PlayerView, _Mapping]] = ..., reward: _Optional[float] = ..., done: _Optional[bool] = ...) -> None: ...
+
+class BatchStepRequest(_message.Message):
+    __slots__ = ("inputs",)
+    INPUTS_FIELD_NUMBER: _ClassVar[int]
+    inputs: _containers.RepeatedCompositeFieldContainer[InputRequest]
+    def __init__(self, inputs: _Optional[_Iterable[_Union[InputRequest, _Mapping]]] = ...) -> None: ...
+
+class BatchStepResult(_message.Message):
+    __slots__ = ("results",)
+    RESULTS_FIELD_NUMBER: _ClassVar[int]
+    results: _containers.RepeatedCompositeFieldContainer[StepResult]
+    def __init__(self, results: _Optional[_Iterable[_Union[StepResult, _Mapping]]] = ...) -> None: ...
+
+class Vector(_message.Message):
+    __slots__ = ("x", "y", "z")
+    X_FIELD_NUMBER: _ClassVar[int]
+    Y_FIELD_NUMBER: _ClassVar[int]
+    Z_FIELD_NUMBER: _ClassVar[int]
     x: float
     y: float
     z: float
-    def __init__(self, surrounding: _Optional[_Iterable[_Union[Terrain, _Mapping]]] = ..., x: _Optional[float] = ..., y: _Optional[float] = ..., z: _Optional[float] = ..., distance: _Optional[float] = ..., finished: bool = ...) -> None: ...
+    def __init__(self, x: _Optional[float] = ..., y: _Optional[float] = ..., z: _Optional[float] = ...) -> None: ...
+
+class Terrain(_message.Message):
+    __slots__ = ("height", "kind", "slope", "normal")
+    HEIGHT_FIELD_NUMBER: _ClassVar[int]
+    KIND_FIELD_NUMBER: _ClassVar[int]
+    SLOPE_FIELD_NUMBER: _ClassVar[int]
+    NORMAL_FIELD_NUMBER: _ClassVar[int]
+    height: float
+    kind: int
+    slope: float
+    normal: Vector
+    def __init__(self, height: _Optional[float] = ..., kind: _Optional[int] = ..., slope: _Optional[float] = ..., normal: _Optional[_Union[Vector, _Mapping]] = ...) -> None: ...
+
+class Scores(_message.Message):
+    __slots__ = ("scores",)
+    SCORES_FIELD_NUMBER: _ClassVar[int]
+    scores: _containers.RepeatedCompositeFieldContainer[Score]
+    def __init__(self, scores: _Optional[_Iterable[_Union[Score, _Mapping]]] = ...) -> None: ...
 
 class Score(_message.Message):
-    __slots__ = ["timings", "total"]
+    __slots__ = ("timings", "total", "name", "position", "frames", "finished", "termination")
     TIMINGS_FIELD_NUMBER: _ClassVar[int]
     TOTAL_FIELD_NUMBER: _ClassVar[int]
+    NAME_FIELD_NUMBER: _ClassVar[int]
+    POSITION_FIELD_NUMBER: _ClassVar[int]
+    FRAMES_FIELD_NUMBER: _ClassVar[int]
+    FINISHED_FIELD_NUMBER: _ClassVar[int]
+    TERMINATION_FIELD_NUMBER: _ClassVar[int]
     timings: _containers.RepeatedScalarFieldContainer[int]
     total: int
-    def __init__(self, timings: _Optional[_Iterable[int]] = ..., total: _Optional[int] = ...) -> None: ...
+    name: str
+    position: int
+    frames: int
+    finished: bool
+    termination: Termination
+    def __init__(self, timings: _Optional[_Iterable[int]] = ..., total: _Optional[int] = ..., name: _Optional[str] = ..., position: _Optional[int] = ..., frames: _Optional[int] = ..., finished: _Optional[bool] = ..., termination: _Optional[_Union[Termination, str]] = ...) -> None: ...
 
-class Terrain(_message.Message):
-    __slots__ = ["height", "kind"]
-    HEIGHT_FIELD_NUMBER: _ClassVar[int]
+class Config(_message.Message):
+    __slots__ = ("control_mode", "max_speed", "physics")
+    CONTROL_MODE_FIELD_NUMBER: _ClassVar[int]
+    MAX_SPEED_FIELD_NUMBER: _ClassVar[int]
+    PHYSICS_FIELD_NUMBER: _ClassVar[int]
+    control_mode: ControlMode
+    max_speed: float
+    physics: Physics
+    def __init__(self, control_mode: _Optional[_Union[ControlMode, str]] = ..., max_speed: _Optional[float] = ..., physics: _Optional[_Union[Physics, _Mapping]] = ...) -> None: ...
+
+class Physics(_message.Message):
+    __slots__ = ("timestep", "substeps", "gravity", "mass", "friction", "restitution", "linear_damping", "angular_damping", "max_velocity", "surfaces")
+    TIMESTEP_FIELD_NUMBER: _ClassVar[int]
+    SUBSTEPS_FIELD_NUMBER: _ClassVar[int]
+    GRAVITY_FIELD_NUMBER: _ClassVar[int]
+    MASS_FIELD_NUMBER: _ClassVar[int]
+    FRICTION_FIELD_NUMBER: _ClassVar[int]
+    RESTITUTION_FIELD_NUMBER: _ClassVar[int]
+    LINEAR_DAMPING_FIELD_NUMBER: _ClassVar[int]
+    ANGULAR_DAMPING_FIELD_NUMBER: _ClassVar[int]
+    MAX_VELOCITY_FIELD_NUMBER: _ClassVar[int]
+    SURFACES_FIELD_NUMBER: _ClassVar[int]
+    timestep: float
+    substeps: int
+    gravity: float
+    mass: float
+    friction: float
+    restitution: float
+    linear_damping: float
+    angular_damping: float
+    max_velocity: float
+    surfaces: _containers.RepeatedCompositeFieldContainer[Surface]
+    def __init__(self, timestep: _Optional[float] = ..., substeps: _Optional[int] = ..., gravity: _Optional[float] = ..., mass: _Optional[float] = ..., friction: _Optional[float] = ..., restitution: _Optional[float] = ..., linear_damping: _Optional[float] = ..., angular_damping: _Optional[float] = ..., max_velocity: _Optional[float] = ..., surfaces: _Optional[_Iterable[_Union[Surface, _Mapping]]] = ...) -> None: ...
+
+class Surface(_message.Message):
+    __slots__ = ("kind", "friction", "rolling_resistance", "max_speed")
     KIND_FIELD_NUMBER: _ClassVar[int]
-    height: float
+    FRICTION_FIELD_NUMBER: _ClassVar[int]
+    ROLLING_RESISTANCE_FIELD_NUMBER: _ClassVar[int]
+    MAX_SPEED_FIELD_NUMBER: _ClassVar[int]
     kind: int
-    def __init__(self, height: _Optional[float] = ..., kind: _Optional[int] = ...) -> None: ...
+    friction: float
+    rolling_resistance: float
+    max_speed: float
+    def __init__(self, kind: _Optional[int] = ..., friction: _Optional[float] = ..., rolling_resistance: _Optional[float] = ..., max_speed: _Optional[float] = ...) -> None: ...
+
+class MapRequest(_message.Message):
+    __slots__ = ("environment", "step")
+    ENVIRONMENT_FIELD_NUMBER: _ClassVar[int]
+    STEP_FIELD_NUMBER: _ClassVar[int]
+    environment: int
+    step: int
+    def __init__(self, environment: _Optional[int] = ..., step: _Optional[int] = ...) -> None: ...
+
+class Map(_message.Message):
+    __slots__ = ("size", "spacing", "origin", "samples")
+    SIZE_FIELD_NUMBER: _ClassVar[int]
+    SPACING_FIELD_NUMBER: _ClassVar[int]
+    ORIGIN_FIELD_NUMBER: _ClassVar[int]
+    SAMPLES_FIELD_NUMBER: _ClassVar[int]
+    size: int
+    spacing: float
+    origin: Vector
+    samples: _containers.RepeatedCompositeFieldContainer[Terrain]
+    def __init__(self, size: _Optional[int] = ..., spacing: _Optional[float] = ..., origin: _Optional[_Union[Vector, _Mapping]] = ..., samples: _Optional[_Iterable[_Union[Terrain, _Mapping]]] = ...) -> None: ...
+
+class Track(_message.Message):
+    __slots__ = ("checkpoints",)
+    CHECKPOINTS_FIELD_NUMBER: _ClassVar[int]
+    checkpoints: _containers.RepeatedCompositeFieldContainer[Checkpoint]
+    def __init__(self, checkpoints: _Optional[_Iterable[_Union[Checkpoint, _Mapping]]] = ...) -> None: ...
+
+class Checkpoint(_message.Message):
+    __slots__ = ("position", "radius")
+    POSITION_FIELD_NUMBER: _ClassVar[int]
+    RADIUS_FIELD_NUMBER: _ClassVar[int]
+    position: Vector
+    radius: float
+    def __init__(self, position: _Optional[_Union[Vector, _Mapping]] = ..., radius: _Optional[float] = ...) -> None: ...
@@ -21,7 +21,7 @@ class MainServiceStub(object):
                 )
         self.getState = channel.unary_unary(
                 '/game.MainService/getState',
-                request_serializer=game__pb2.Empty.SerializeToString,
+                request_serializer=game__pb2.StateRequest.SerializeToString,
                 response_deserializer=game__pb2.PlayerView.FromString,
                 )
         self.input = channel.unary_unary(
@@ -36,8 +36,43 @@ class MainServiceStub(object):
                 )
         self.getScore = channel.unary_unary(
                 '/game.MainService/getScore',
+                request_serializer=game__pb2.EnvironmentRequest.SerializeToString,
+                response_deserializer=game__pb2.Scores.FromString,
+                )
+        self.reset = channel.unary_unary(
+                '/game.MainService/reset',
+                request_serializer=game__pb2.ResetRequest.SerializeToString,
+                response_deserializer=game__pb2.Empty.FromString,
+                )
+        self.step = channel.unary_unary(
+                '/game.MainService/step',
+                request_serializer=game__pb2.InputRequest.SerializeToString,
+                response_deserializer=game__pb2.StepResult.FromString,
+                )
+        self.batchStep = channel.unary_unary(
+                '/game.MainService/batchStep',
+                request_serializer=game__pb2.BatchStepRequest.SerializeToString,
+                response_deserializer=game__pb2.BatchStepResult.FromString,
+                )
+        self.session = channel.stream_stream(
+                '/game.MainService/session',
+                request_serializer=game__pb2.InputRequest.SerializeToString,
+                response_deserializer=game__pb2.PlayerView.FromString,
+                )
+        self.getConfig = channel.unary_unary(
+                '/game.MainService/getConfig',
                 request_serializer=game__pb2.Empty.SerializeToString,
-                response_deserializer=game__pb2.Score.FromString,
+                response_deserializer=game__pb2.Config.FromString,
+                )
+        self.getMap = channel.unary_unary(
+                '/game.MainService/getMap',
+                request_serializer=game__pb2.MapRequest.SerializeToString,
+                response_deserializer=game__pb2.Map.FromString,
+                )
+        self.getTrack = channel.unary_unary(
+                '/game.MainService/getTrack',
+                request_serializer=game__pb2.EnvironmentRequest.SerializeToString,
+                response_deserializer=game__pb2.Track.FromString,
                 )
 
 
@@ -74,6 +109,48 @@ class MainServiceServicer(object):
         context.set_details('Method not implemented!')
         raise NotImplementedError('Method not implemented!')
 
+    def reset(self, request, context):
+        """Missing associated documentation comment in .proto file."""
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def step(self, request, context):
+        """Missing associated documentation comment in .proto file."""
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def batchStep(self, request, context):
+        """Missing associated documentation comment in .proto file."""
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def session(self, request_iterator, context):
+        """Missing associated documentation comment in .proto file."""
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def getConfig(self, request, context):
+        """Missing associated documentation comment in .proto file."""
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def getMap(self, request, context):
+        """Missing associated documentation comment in .proto file."""
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def getTrack(self, request, context):
+        """Missing associated documentation comment in .proto file."""
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
 
 def add_MainServiceServicer_to_server(servicer, server):
     rpc_method_handlers = {
@@ -84,7 +161,7 @@ def add_MainServiceServicer_to_server(servicer, server):
             ),
             'getState': grpc.unary_unary_rpc_method_handler(
                     servicer.getState,
-                    request_deserializer=game__pb2.Empty.FromString,
+                    request_deserializer=game__pb2.StateRequest.FromString,
                     response_serializer=game__pb2.PlayerView.SerializeToString,
             ),
             'input': grpc.unary_unary_rpc_method_handler(
@@ -99,8 +176,43 @@ def add_MainServiceServicer_to_server(servicer, server):
             ),
             'getScore': grpc.unary_unary_rpc_method_handler(
                     servicer.getScore,
+                    request_deserializer=game__pb2.EnvironmentRequest.FromString,
+                    response_serializer=game__pb2.Scores.SerializeToString,
+            ),
+            'reset': grpc.unary_unary_rpc_method_handler(
+                    servicer.reset,
+                    request_deserializer=game__pb2.ResetRequest.FromString,
+                    response_serializer=game__pb2.Empty.SerializeToString,
+            ),
+            'step': grpc.unary_unary_rpc_method_handler(
+                    servicer.step,
+                    request_deserializer=game__pb2.InputRequest.FromString,
+                    response_serializer=game__pb2.StepResult.SerializeToString,
+            ),
+            'batchStep': grpc.unary_unary_rpc_method_handler(
+                    servicer.batchStep,
+                    request_deserializer=game__pb2.BatchStepRequest.FromString,
+                    response_serializer=game__pb2.BatchStepResult.SerializeToString,
+            ),
+            'session': grpc.stream_stream_rpc_method_handler(
+                    servicer.session,
+                    request_deserializer=game__pb2.InputRequest.FromString,
+                    response_serializer=game__pb2.PlayerView.SerializeToString,
+            ),
+            'getConfig': grpc.unary_unary_rpc_method_handler(
+                    servicer.getConfig,
                     request_deserializer=game__pb2.Empty.FromString,
-                    response_serializer=game__pb2.Score.SerializeToString,
+                    response_serializer=game__pb2.Config.SerializeToString,
+            ),
+            'getMap': grpc.unary_unary_rpc_method_handler(
+                    servicer.getMap,
+                    request_deserializer=game__pb2.MapRequest.FromString,
+                    response_serializer=game__pb2.Map.SerializeToString,
+            ),
+            'getTrack': grpc.unary_unary_rpc_method_handler(
+                    servicer.getTrack,
+                    request_deserializer=game__pb2.EnvironmentRequest.FromString,
+                    response_serializer=game__pb2.Track.SerializeToString,
             ),
     }
     generic_handler = grpc.method_handlers_generic_handler(
@@ -141,7 +253,7 @@ class MainService(object):
             timeout=None,
             metadata=None):
         return grpc.experimental.unary_unary(request, target, '/game.MainService/getState',
-            game__pb2.Empty.SerializeToString,
+            game__pb2.StateRequest.SerializeToString,
             game__pb2.PlayerView.FromString,
             options, channel_credentials,
             insecure, call_credentials, compression, wait_for_ready, timeout, metadata)
@@ -192,7 +304,126 @@ class MainService(object):
             timeout=None,
             metadata=None):
         return grpc.experimental.unary_unary(request, target, '/game.MainService/getScore',
+            game__pb2.EnvironmentRequest.SerializeToString,
+            game__pb2.Scores.FromString,
+            options, channel_credentials,
+            insecure, call_credentials, compression, wait_for_ready, timeout, metadata)
+
+    @staticmethod
+    def reset(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(request, target, '/game.MainService/reset',
+            game__pb2.ResetRequest.SerializeToString,
+            game__pb2.Empty.FromString,
+            options, channel_credentials,
+            insecure, call_credentials, compression, wait_for_ready, timeout, metadata)
+
+    @staticmethod
+    def step(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(request, target, '/game.MainService/step',
+            game__pb2.InputRequest.SerializeToString,
+            game__pb2.StepResult.FromString,
+            options, channel_credentials,
+            insecure, call_credentials, compression, wait_for_ready, timeout, metadata)
+
+    @staticmethod
+    def batchStep(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(request, target, '/game.MainService/batchStep',
+            game__pb2.BatchStepRequest.SerializeToString,
+            game__pb2.BatchStepResult.FromString,
+            options, channel_credentials,
+            insecure, call_credentials, compression, wait_for_ready, timeout, metadata)
+
+    @staticmethod
+    def session(request_iterator,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.stream_stream(request_iterator, target, '/game.MainService/session',
+            game__pb2.InputRequest.SerializeToString,
+            game__pb2.PlayerView.FromString,
+            options, channel_credentials,
+            insecure, call_credentials, compression, wait_for_ready, timeout, metadata)
+
+    @staticmethod
+    def getConfig(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(request, target, '/game.MainService/getConfig',
             game__pb2.Empty.SerializeToString,
-            game__pb2.Score.FromString,
+            game__pb2.Config.FromString,
+            options, channel_credentials,
+            insecure, call_credentials, compression, wait_for_ready, timeout, metadata)
+
+    @staticmethod
+    def getMap(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(request, target, '/game.MainService/getMap',
+            game__pb2.MapRequest.SerializeToString,
+            game__pb2.Map.FromString,
+            options, channel_credentials,
+            insecure, call_credentials, compression, wait_for_ready, timeout, metadata)
+
+    @staticmethod
+    def getTrack(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(request, target, '/game.MainService/getTrack',
+            game__pb2.EnvironmentRequest.SerializeToString,
+            game__pb2.Track.FromString,
             options, channel_credentials,
             insecure, call_credentials, compression, wait_for_ready, timeout, metadata)
@@ -0,0 +1,110 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, VertexAttributeValues},
+};
+use image::{GrayImage, ImageBuffer, Luma};
+
+use crate::world::{
+    generate_world, load_texture::setup_untextured_atlas, terrain::Terrain, WorldConfig,
+};
+
+/// Generates the world of the seed and writes it into the directory out:
+/// * heightmap.png: 16 bit grayscale, one pixel per terrain sample, black is the minimum and white the maximum height
+/// * kinds.png: 8 bit grayscale, the kind of each terrain sample as in the kind of the grpc interface
+/// * world.json: seed, size and the height range of the heightmap
+/// * checkpoints.json: the checkpoint positions in order of collection
+/// * terrain.obj: the terrain mesh
+pub fn export(out: &Path, seed: u32, size: f32, scale: f32, config: &WorldConfig) {
+    std::fs::create_dir_all(out).unwrap();
+    let (terrain, track) = generate_world(seed, size, scale, config);
+
+    let (heightmap, min_height, max_height) = heightmap(&terrain);
+    heightmap.save(out.join("heightmap.png")).unwrap();
+    kind_map(&terrain).save(out.join("kinds.png")).unwrap();
+
+    let world = serde_json::json!({
+        "seed": seed,
+        "world_size": size,
+        "meters_per_sample": scale,
+        "samples": terrain.samples(),
+        "min_height": min_height,
+        "max_height": max_height,
+    });
+    std::fs::write(
+        out.join("world.json"),
+        serde_json::to_string_pretty(&world).unwrap(),
+    )
+    .unwrap();
+
+    let checkpoints: Vec<[f32; 3]> = track.checkpoints.iter().map(|c| c.to_array()).collect();
+    std::fs::write(
+        out.join("checkpoints.json"),
+        serde_json::to_string_pretty(&checkpoints).unwrap(),
+    )
+    .unwrap();
+
+    write_obj(
+        &terrain.to_mesh(&setup_untextured_atlas()),
+        &out.join("terrain.obj"),
+    );
+}
+
+/// x runs along the x axis of the image and z along its y axis, like the heightmaps of the world config.
+fn heightmap(terrain: &Terrain) -> (ImageBuffer<Luma<u16>, Vec<u16>>, f32, f32) {
+    let samples = terrain.samples();
+    let (min, max) = (0..samples)
+        .flat_map(|x| (0..samples).map(move |z| (x, z)))
+        .map(|(x, z)| terrain.get(x, z).height)
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), h| {
+            (min.min(h), max.max(h))
+        });
+    let range = (max - min).max(f32::EPSILON);
+    let image = ImageBuffer::from_fn(samples as u32, samples as u32, |x, z| {
+        let height = terrain.get(x as usize, z as usize).height;
+        Luma([((height - min) / range * u16::MAX as f32).round() as u16])
+    });
+    (image, min, max)
+}
+
+fn kind_map(terrain: &Terrain) -> GrayImage {
+    let samples = terrain.samples() as u32;
+    GrayImage::from_fn(samples, samples, |x, z| {
+        Luma([terrain.get(x as usize, z as usize).texture as u8])
+    })
+}
+
+fn write_obj(mesh: &Mesh, path: &Path) {
+    let mut file = BufWriter::new(File::create(path).unwrap());
+    if let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    {
+        for [x, y, z] in positions {
+            writeln!(file, "v {x} {y} {z}").unwrap();
+        }
+    }
+    if let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+        for [u, v] in uvs {
+            writeln!(file, "vt {u} {v}").unwrap();
+        }
+    }
+    if let Some(VertexAttributeValues::Float32x3(normals)) = mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+    {
+        for [x, y, z] in normals {
+            writeln!(file, "vn {x} {y} {z}").unwrap();
+        }
+    }
+    if let Some(Indices::U32(indices)) = mesh.indices() {
+        // obj indices start at 1, positions, uvs and normals share the same index
+        for triangle in indices.chunks(3) {
+            let [a, b, c] = [triangle[0] + 1, triangle[1] + 1, triangle[2] + 1];
+            writeln!(file, "f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c}").unwrap();
+        }
+    }
+    file.flush().unwrap();
+}
@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::world::{load_texture::TextureSections, terrain::Terrain};
+
+/// A fan of rays cast from the center of a player, spread evenly around its heading.
+#[derive(Debug, Clone, Copy)]
+pub struct Lidar {
+    pub rays: usize,
+    /// opening angle of the fan in degrees
+    pub fov: f32,
+    /// maximum length of a ray in meters
+    pub range: f32,
+    /// vertical angle of all rays in degrees, negative values point to the ground
+    pub pitch: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum LidarHit {
+    Terrain(f32, TextureSections),
+    Player(f32),
+    Nothing,
+}
+
+impl Lidar {
+    /// Casts all rays from right to left. Sensors (checkpoints, recorded players) and the player itself are ignored.
+    pub fn cast(
+        &self,
+        context: &RapierContext,
+        terrain: &Terrain,
+        players: &[Entity],
+        player: Entity,
+        origin: Vec3,
+        forward: Vec2,
+    ) -> Vec<LidarHit> {
+        let filter = QueryFilter::default()
+            .exclude_sensors()
+            .exclude_rigid_body(player);
+        let heading = forward.y.atan2(forward.x);
+        let pitch = self.pitch.to_radians();
+        let fov = self.fov.to_radians();
+        (0..self.rays)
+            .map(|i| {
+                let offset = if self.rays == 1 {
+                    0.0
+                } else {
+                    fov / 2.0 - fov * i as f32 / (self.rays - 1) as f32
+                };
+                let yaw = heading + offset;
+                let direction = Vec3::new(
+                    yaw.cos() * pitch.cos(),
+                    pitch.sin(),
+                    yaw.sin() * pitch.cos(),
+                );
+                match context.cast_ray(origin, direction, self.range, true, filter) {
+                    Some((entity, distance)) if players.contains(&entity) => {
+                        LidarHit::Player(distance)
+                    }
+                    Some((_, distance)) => {
+                        let hit = origin + direction * distance;
+                        terrain
+                            .get_kind(hit.x, hit.z)
+                            .map(|kind| LidarHit::Terrain(distance, kind))
+                            .unwrap_or(LidarHit::Nothing)
+                    }
+                    None => LidarHit::Nothing,
+                }
+            })
+            .collect()
+    }
+}
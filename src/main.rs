@@ -5,39 +5,55 @@ use std::{
 };
 
 use bevy::{
+    app::ScheduleRunnerPlugin,
     audio::AudioPlugin,
     core_pipeline::CorePipelinePlugin,
     diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
     gltf::GltfPlugin,
+    log::LogPlugin,
     pbr::PbrPlugin,
     prelude::*,
     render::RenderPlugin,
     sprite::SpritePlugin,
     text::TextPlugin,
     ui::UiPlugin,
+    winit::WinitPlugin,
 };
 use bevy_rapier3d::prelude::*;
 use camera::CameraPlugin;
-use clap::Parser;
-use player::PlayerPlugin;
-use server::{start_server, FrameState, NextFrame};
+use clap::{Parser, Subcommand};
+use physics::{configure_physics, PhysicsConfig};
+use player::{Control, ControlMode, PlayerPlugin};
+use server::{start_server, EnvironmentConnection, FrameState, NextFrame, PlayerObservation};
 use tokio::{
     runtime::Runtime,
     sync::mpsc::{Receiver, Sender},
 };
-use world::{checkpoint::History, WorldPlugin};
+use world::{
+    checkpoint::{History, TrackPreset},
+    reward::RewardWeights,
+    termination::TerminationRules,
+    terrain::Terrain,
+    Track, WorldConfig, WorldPlugin,
+};
 
 mod camera;
+mod export;
+mod lidar;
+mod physics;
 mod player;
 mod server;
 mod texture;
 mod world;
 
 #[derive(Parser, Clone, Debug)]
+#[command(subcommand_negates_reqs = true)]
 struct Opt {
+    #[command(subcommand)]
+    command: Option<Command>,
     /// port used to start the grpc server
-    #[arg(long)]
-    port: i32,
+    #[arg(long, required = true)]
+    port: Option<i32>,
     /// if passed, the game will not be rendered.
     #[arg(long)]
     headless: bool,
@@ -54,6 +70,132 @@ struct Opt {
     #[arg(long)]
     /// Path under which to save a recoding.
     save: Option<PathBuf>,
+    /// number of players controlled via grpc, each request addresses a player by its id (0..players).
+    /// A frame is only simulated once every player submitted an input.
+    #[arg(long, default_value_t = 1)]
+    players: usize,
+    /// number of independent worlds hosted in this process, each request addresses a world by its id (0..environments).
+    /// Requires --headless, recordings are only saved for environment 0.
+    #[arg(long, default_value_t = 1)]
+    environments: usize,
+    /// json file with the weights of the built-in reward (progress, checkpoint, time, off_road), missing weights use the defaults.
+    #[arg(long)]
+    reward_config: Option<PathBuf>,
+    /// reward per meter the player got closer to the next checkpoint, overrides the config file
+    #[arg(long)]
+    reward_progress: Option<f32>,
+    /// reward per collected checkpoint, overrides the config file
+    #[arg(long)]
+    reward_checkpoint: Option<f32>,
+    /// penalty per frame, overrides the config file
+    #[arg(long)]
+    reward_time: Option<f32>,
+    /// penalty per frame the player is not on the road, overrides the config file
+    #[arg(long)]
+    reward_off_road: Option<f32>,
+    /// ends the race of a player after this many frames
+    #[arg(long)]
+    max_frames: Option<usize>,
+    /// ends the race of a player that leaves the terrain
+    #[arg(long)]
+    terminate_off_terrain: bool,
+    /// ends the race of a player that falls below this height
+    #[arg(long, allow_negative_numbers = true)]
+    min_height: Option<f32>,
+    /// ends the race of a player that did not collect a checkpoint for this many frames
+    #[arg(long)]
+    stuck_frames: Option<usize>,
+    /// how the inputs move the player
+    #[arg(long, value_enum, default_value_t = ControlMode::Velocity)]
+    control: ControlMode,
+    /// horizontal speed in meters per second up to which the acceleration control mode accelerates
+    #[arg(long, default_value_t = 10.0)]
+    max_speed: f32,
+    /// json file with the physics parameters (timestep, substeps, gravity, mass, friction, restitution, linear_damping, angular_damping, max_velocity) and the surfaces of the ground kinds (friction, rolling_resistance, max_speed), missing parameters use the defaults.
+    #[arg(long)]
+    physics_config: Option<PathBuf>,
+    /// edge length of the square world in meters
+    #[arg(long, default_value_t = 430.0)]
+    world_size: f32,
+    /// meters between two height samples of the terrain, smaller values give a finer terrain mesh
+    #[arg(long, default_value_t = 1.0)]
+    meters_per_sample: f32,
+    /// json file describing how the terrain is generated (layers, heightmap, erosion, biomes, track), missing values use the defaults
+    #[arg(long)]
+    world_config: Option<PathBuf>,
+    /// skips the erosion of the world config, which speeds up the generation of new worlds
+    #[arg(long)]
+    skip_erosion: bool,
+    /// difficulty of the track, replaces the track of the world config
+    #[arg(long, value_enum)]
+    track_preset: Option<TrackPreset>,
+    /// disables getMap and getTrack, so that players only know the terrain around them
+    #[arg(long)]
+    blind: bool,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+enum Command {
+    /// Writes the world of the seed (heightmap, kind map, checkpoints and terrain mesh) into a directory without starting the game.
+    /// Uses the world options (seed, world size, meters per sample, world config) of the game.
+    Export {
+        /// directory for the exported files, created if missing
+        #[arg(long)]
+        out: PathBuf,
+    },
+}
+
+impl Opt {
+    fn control(&self) -> Control {
+        Control {
+            mode: self.control,
+            max_speed: self.max_speed,
+        }
+    }
+
+    fn physics(&self) -> PhysicsConfig {
+        self.physics_config
+            .as_ref()
+            .map(|path| {
+                let j = std::fs::read_to_string(path).unwrap();
+                serde_json::from_str(&j).unwrap()
+            })
+            .unwrap_or_default()
+    }
+
+    fn world_config(&self) -> WorldConfig {
+        let mut config: WorldConfig = self
+            .world_config
+            .as_ref()
+            .map(|path| {
+                let j = std::fs::read_to_string(path).unwrap();
+                serde_json::from_str(&j).unwrap()
+            })
+            .unwrap_or_default();
+        if self.skip_erosion {
+            config.erosion = None;
+        }
+        if let Some(preset) = self.track_preset {
+            config.track = preset.config();
+        }
+        config
+    }
+
+    fn reward_weights(&self) -> RewardWeights {
+        let mut weights: RewardWeights = self
+            .reward_config
+            .as_ref()
+            .map(|path| {
+                let j = std::fs::read_to_string(path).unwrap();
+                serde_json::from_str(&j).unwrap()
+            })
+            .unwrap_or_default();
+        weights.progress = self.reward_progress.unwrap_or(weights.progress);
+        weights.checkpoint = self.reward_checkpoint.unwrap_or(weights.checkpoint);
+        weights.time = self.reward_time.unwrap_or(weights.time);
+        weights.off_road = self.reward_off_road.unwrap_or(weights.off_road);
+        weights
+    }
 }
 
 #[derive(clap::ValueEnum, Debug, Clone)]
@@ -68,11 +210,17 @@ enum PlayerColor {
 }
 
 #[derive(Resource)]
-pub struct RuntimeResoure(pub Runtime);
+pub struct RuntimeResoure(pub Arc<Runtime>);
 #[derive(Resource)]
-pub struct FrameStateSenderResource(pub Sender<FrameState>);
+pub struct FrameStateSenderResource(pub Sender<Vec<FrameState>>);
 #[derive(Resource)]
 pub struct HistoryResource(pub Arc<Mutex<HashMap<Entity, History>>>);
+/// Observation settings per player, indexed by the player id. Changed by the server, read when the states are built.
+#[derive(Resource)]
+pub struct ObservationResource(pub Arc<Mutex<Vec<PlayerObservation>>>);
+/// Copy of the current world for the server, replaced by the game whenever the world is regenerated.
+#[derive(Resource)]
+pub struct MapResource(pub Arc<Mutex<Option<(Arc<Terrain>, Arc<Track>)>>>);
 #[derive(Resource)]
 pub struct NextFrameResource(pub Receiver<NextFrame>);
 
@@ -82,52 +230,149 @@ pub struct ShutdownResource(pub Receiver<()>);
 #[derive(Resource)]
 pub struct SavePathReource(pub Option<PathBuf>);
 
+/// Index of the environment the app simulates.
+#[derive(Resource)]
+pub struct EnvironmentIdResource(pub usize);
+
+/// The game side ends of the channels to one environment.
+struct EnvironmentResources {
+    frame_sender: Sender<Vec<FrameState>>,
+    next_frame_receiver: Receiver<NextFrame>,
+    shutdown_receiver: Receiver<()>,
+    history: Arc<Mutex<HashMap<Entity, History>>>,
+    observations: Arc<Mutex<Vec<PlayerObservation>>>,
+    map: Arc<Mutex<Option<(Arc<Terrain>, Arc<Track>)>>>,
+}
+
 fn main() {
     let opt = dbg!(Opt::parse());
-    let runtime = Runtime::new().unwrap();
+    assert!(
+        opt.headless || opt.environments == 1,
+        "multiple environments are only supported in headless mode"
+    );
+    assert!(
+        opt.meters_per_sample > 0.0 && opt.world_size >= opt.meters_per_sample,
+        "the world has to be at least one sample wide"
+    );
+    if let Some(Command::Export { out }) = &opt.command {
+        export::export(
+            out,
+            opt.seed,
+            opt.world_size,
+            opt.meters_per_sample,
+            &opt.world_config(),
+        );
+        return;
+    }
+    let runtime = Arc::new(Runtime::new().unwrap());
+    let (connections, environments): (Vec<_>, Vec<_>) =
+        (0..opt.environments).map(|_| connect(&opt)).unzip();
+    let t = start_server(
+        connections,
+        opt.port.unwrap(),
+        opt.players,
+        opt.control(),
+        opt.physics(),
+        opt.blind,
+    );
+
+    let mut environments = environments.into_iter().enumerate();
+    let (_, main_environment) = environments.next().unwrap();
+    let threads: Vec<_> = environments
+        .map(|(i, environment)| {
+            let opt = opt.clone();
+            let runtime = runtime.clone();
+            std::thread::spawn(move || build_app(opt, environment, runtime, i).run())
+        })
+        .collect();
+    build_app(opt, main_environment, runtime, 0).run();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    t.join().unwrap();
+}
+
+/// Creates the channels between the server and the game of one environment.
+fn connect(opt: &Opt) -> (EnvironmentConnection, EnvironmentResources) {
     let (frame_sender, frame_reciever) = tokio::sync::mpsc::channel(1);
     let (next_sender, next_reciever) = tokio::sync::mpsc::channel(1);
     let (shutdown_sender, shutdown_receiver) = tokio::sync::mpsc::channel(1);
-
     let history = Arc::new(Mutex::new(HashMap::<Entity, History>::with_capacity(
-        opt.recording.len() + 1,
+        opt.recording.len() + opt.players,
     )));
-    let t = start_server(
-        frame_reciever,
-        next_sender,
-        shutdown_sender,
-        history.clone(),
-        opt.port,
-    );
+    let observations = Arc::new(Mutex::new(vec![PlayerObservation::default(); opt.players]));
+    let map = Arc::new(Mutex::new(None));
+    (
+        EnvironmentConnection {
+            frame_receiver: frame_reciever,
+            next_frame_sender: next_sender,
+            shutdown_sender,
+            history: history.clone(),
+            observations: observations.clone(),
+            map: map.clone(),
+        },
+        EnvironmentResources {
+            frame_sender,
+            next_frame_receiver: next_reciever,
+            shutdown_receiver,
+            history,
+            observations,
+            map,
+        },
+    )
+}
+
+fn build_app(
+    opt: Opt,
+    environment: EnvironmentResources,
+    runtime: Arc<Runtime>,
+    environment_id: usize,
+) -> App {
+    let reward = opt.reward_weights();
+    let control = opt.control();
+    let physics = opt.physics();
+    let world_config = opt.world_config();
     let mut a = App::new();
-    a.insert_resource(NextFrameResource(next_reciever))
-        .insert_resource(HistoryResource(history))
-        .insert_resource(FrameStateSenderResource(frame_sender))
+    a.insert_resource(NextFrameResource(environment.next_frame_receiver))
+        .insert_resource(HistoryResource(environment.history))
+        .insert_resource(ObservationResource(environment.observations))
+        .insert_resource(MapResource(environment.map))
+        .insert_resource(FrameStateSenderResource(environment.frame_sender))
         .insert_resource(RuntimeResoure(runtime))
-        .insert_resource(ShutdownResource(shutdown_receiver))
-        .insert_resource(SavePathReource(opt.save));
+        .insert_resource(physics)
+        .insert_resource(ShutdownResource(environment.shutdown_receiver))
+        .insert_resource(SavePathReource(opt.save.filter(|_| environment_id == 0)))
+        .insert_resource(EnvironmentIdResource(environment_id));
     if opt.headless {
-        a.add_plugins(
-            DefaultPlugins
-                .set(WindowPlugin {
-                    add_primary_window: false,
-                    exit_on_all_closed: false,
-                    ..Default::default()
-                })
-                .build()
-                .disable::<AudioPlugin>()
-                .disable::<RenderPlugin>()
-                .disable::<PbrPlugin>()
-                .disable::<SpritePlugin>()
-                .disable::<TextPlugin>()
-                .disable::<UiPlugin>()
-                .disable::<GltfPlugin>()
-                .disable::<AnimationPlugin>()
-                .disable::<CorePipelinePlugin>()
-                .disable::<GilrsPlugin>(),
-        )
-        .add_asset::<Mesh>()
-        .add_asset::<StandardMaterial>();
+        // without winit, so that several environments can run on their own threads
+        let plugins = DefaultPlugins
+            .set(WindowPlugin {
+                add_primary_window: false,
+                exit_on_all_closed: false,
+                ..Default::default()
+            })
+            .build()
+            .disable::<WinitPlugin>()
+            .disable::<AudioPlugin>()
+            .disable::<RenderPlugin>()
+            .disable::<PbrPlugin>()
+            .disable::<SpritePlugin>()
+            .disable::<TextPlugin>()
+            .disable::<UiPlugin>()
+            .disable::<GltfPlugin>()
+            .disable::<AnimationPlugin>()
+            .disable::<CorePipelinePlugin>()
+            .disable::<GilrsPlugin>();
+        // the logger is global, the first environment sets it up for all of them
+        let plugins = if environment_id == 0 {
+            plugins
+        } else {
+            plugins.disable::<LogPlugin>()
+        };
+        a.add_plugins(plugins)
+            .add_plugin(ScheduleRunnerPlugin)
+            .add_asset::<Mesh>()
+            .add_asset::<StandardMaterial>();
     } else {
         a.add_plugins(DefaultPlugins).add_plugin(CameraPlugin {
             active: !opt.headless,
@@ -135,24 +380,64 @@ fn main() {
     }
     a.add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
         .add_startup_system(configure_physics)
-        .add_plugin(WorldPlugin { seed: opt.seed })
+        .add_plugin(WorldPlugin {
+            seed: opt.seed,
+            headless: opt.headless,
+            reward,
+            termination: TerminationRules {
+                max_frames: opt.max_frames,
+                leave_terrain: opt.terminate_off_terrain,
+                min_height: opt.min_height,
+                stuck_frames: opt.stuck_frames,
+            },
+            size: opt.world_size,
+            scale: opt.meters_per_sample,
+            config: world_config,
+        })
         .add_plugin(PlayerPlugin {
             grpc: opt.headless,
+            players: opt.players,
             recording_paths: opt.recording,
             materials: opt.player_image.into_iter().map(|v| v.into()).collect(),
+            control,
+            physics,
         })
         .add_plugin(FrameTimeDiagnosticsPlugin::default())
         .add_plugin(LogDiagnosticsPlugin {
             ..Default::default()
         });
-
-    a.run();
-    t.join().unwrap();
+    a
 }
 
-fn configure_physics(mut config: ResMut<RapierConfiguration>) {
-    config.timestep_mode = TimestepMode::Fixed {
-        dt: 0.016,
-        substeps: 1,
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn several_environments_can_be_built() {
+        let opt = Opt::parse_from([
+            "artificial_bike_racing",
+            "--port",
+            "50051",
+            "--seed",
+            "1",
+            "--headless",
+            "--environments",
+            "2",
+        ]);
+        let runtime = Arc::new(Runtime::new().unwrap());
+        // like in main, every environment gets its own thread
+        let threads: Vec<_> = (0..opt.environments)
+            .map(|i| {
+                let (_, environment) = connect(&opt);
+                let (opt, runtime) = (opt.clone(), runtime.clone());
+                std::thread::spawn(move || {
+                    build_app(opt, environment, runtime, i);
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    }
 }
@@ -0,0 +1,168 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    player::PlayerMarker,
+    world::{load_texture::TextureSections, terrain::Terrain},
+};
+
+/// Parameters of the physics simulation and the player spheres.
+#[derive(Resource, Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct PhysicsConfig {
+    /// simulated seconds per frame
+    pub timestep: f32,
+    pub substeps: usize,
+    /// acceleration along the y axis in meters per second squared
+    pub gravity: f32,
+    /// mass of a player sphere in kilograms
+    pub mass: f32,
+    pub friction: f32,
+    pub restitution: f32,
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+    /// maximum speed of a player in meters per second, unlimited if not set
+    pub max_velocity: Option<f32>,
+    pub surfaces: Surfaces,
+}
+
+/// How the ground under a player changes its movement.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct Surface {
+    /// multiplied with the friction of the player sphere
+    pub friction: f32,
+    /// deceleration of the player as a fraction of the gravity
+    pub rolling_resistance: f32,
+    /// maximum horizontal speed on this surface in meters per second, unlimited if not set
+    pub max_speed: Option<f32>,
+}
+
+impl Default for Surface {
+    fn default() -> Self {
+        Self {
+            friction: 1.0,
+            rolling_resistance: 0.0,
+            max_speed: None,
+        }
+    }
+}
+
+/// One surface per ground kind, the road is made of rock.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct Surfaces {
+    pub grass: Surface,
+    pub grass2: Surface,
+    pub gravel: Surface,
+    pub rock: Surface,
+    pub snow: Surface,
+}
+
+impl Surfaces {
+    pub fn get(&self, kind: TextureSections) -> &Surface {
+        match kind {
+            TextureSections::Grass => &self.grass,
+            TextureSections::Grass2 => &self.grass2,
+            TextureSections::Gravel => &self.gravel,
+            TextureSections::Rock => &self.rock,
+            TextureSections::Snow => &self.snow,
+        }
+    }
+}
+
+impl Default for Surfaces {
+    fn default() -> Self {
+        let grass = Surface {
+            friction: 1.0,
+            rolling_resistance: 0.02,
+            max_speed: None,
+        };
+        Self {
+            grass,
+            grass2: grass,
+            gravel: Surface {
+                friction: 1.2,
+                rolling_resistance: 0.08,
+                max_speed: Some(8.0),
+            },
+            rock: Surface::default(),
+            snow: Surface {
+                friction: 0.2,
+                rolling_resistance: 0.01,
+                max_speed: None,
+            },
+        }
+    }
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            timestep: 0.016,
+            substeps: 1,
+            gravity: -9.81,
+            // a sphere with radius 0.5 and density 1
+            mass: 4.0 / 3.0 * std::f32::consts::PI * 0.125,
+            friction: 0.5,
+            restitution: 1.0,
+            linear_damping: 0.0,
+            angular_damping: 0.0,
+            max_velocity: None,
+            surfaces: Surfaces::default(),
+        }
+    }
+}
+
+pub fn configure_physics(mut config: ResMut<RapierConfiguration>, physics: Res<PhysicsConfig>) {
+    config.timestep_mode = TimestepMode::Fixed {
+        dt: physics.timestep,
+        substeps: physics.substeps,
+    };
+    config.gravity = Vec3::Y * physics.gravity;
+}
+
+pub fn clamp_velocity(
+    physics: Res<PhysicsConfig>,
+    mut players: Query<&mut Velocity, With<PlayerMarker>>,
+) {
+    let Some(max_velocity) = physics.max_velocity else {
+        return;
+    };
+    for mut velocity in players.iter_mut() {
+        velocity.linvel = velocity.linvel.clamp_length_max(max_velocity);
+    }
+}
+
+/// Applies the surface under every player that touches the ground.
+pub fn apply_surface(
+    physics: Res<PhysicsConfig>,
+    terrain: Res<Terrain>,
+    mut players: Query<(&Transform, &mut Velocity, &mut Friction), With<PlayerMarker>>,
+) {
+    for (transform, mut velocity, mut friction) in players.iter_mut() {
+        let position = transform.translation;
+        let (Some(kind), Some(height)) = (
+            terrain.get_kind(position.x, position.z),
+            terrain.height_at(position.x, position.z),
+        ) else {
+            continue;
+        };
+        // the radius of the sphere plus some tolerance
+        if position.y - height > 1.0 {
+            continue;
+        }
+        let surface = physics.surfaces.get(kind);
+        friction.coefficient = physics.friction * surface.friction;
+
+        let horizontal = Vec2::new(velocity.linvel.x, velocity.linvel.z);
+        let speed = horizontal.length();
+        let deceleration = surface.rolling_resistance * physics.gravity.abs() * physics.timestep;
+        let speed = (speed - deceleration)
+            .max(0.0)
+            .min(surface.max_speed.unwrap_or(f32::INFINITY));
+        let horizontal = horizontal.normalize_or_zero() * speed;
+        velocity.linvel = Vec3::new(horizontal.x, velocity.linvel.y, horizontal.y);
+    }
+}
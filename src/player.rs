@@ -1,6 +1,7 @@
 use std::{fs::OpenOptions, io::Write, path::PathBuf};
 
 use bevy::{
+    app::AppExit,
     prelude::{shape::Icosphere, *},
     render::view::NoFrustumCulling,
 };
@@ -9,16 +10,47 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     camera::FollowCamera,
-    server::FrameState,
-    world::{checkpoint::Checkpoint, terrain::Terrain},
-    FrameStateSenderResource, HistoryResource, NextFrameResource, RuntimeResoure, SavePathReource,
-    ShutdownResource,
+    physics::{apply_surface, clamp_velocity, PhysicsConfig},
+    server::{FrameState, NextFrame, PlayerInput},
+    world::{
+        checkpoint::{checkpoint_collection, Checkpoint, FrameCounter},
+        reward::{compute_rewards, Reward},
+        termination::check_termination,
+        terrain::Terrain,
+        ResetEvent,
+    },
+    EnvironmentIdResource, FrameStateSenderResource, HistoryResource, NextFrameResource,
+    ObservationResource, RuntimeResoure, SavePathReource, ShutdownResource,
 };
 
 pub struct PlayerPlugin {
     pub grpc: bool,
+    /// number of players controlled via grpc
+    pub players: usize,
     pub recording_paths: Vec<PathBuf>,
     pub materials: Vec<PathBuf>,
+    pub control: Control,
+    pub physics: PhysicsConfig,
+}
+
+/// How the inputs of a grpc client move its player.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlMode {
+    /// overrides the horizontal velocity
+    Velocity,
+    /// applies a force to the center of the sphere
+    Force,
+    /// applies a torque, so that the sphere rolls into the input direction
+    Torque,
+    /// accelerates the sphere up to the max speed
+    Acceleration,
+}
+
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct Control {
+    pub mode: ControlMode,
+    /// horizontal speed in meters per second up to which the acceleration mode accelerates
+    pub max_speed: f32,
 }
 
 #[derive(Serialize, Deserialize, Resource)]
@@ -46,6 +78,8 @@ impl From<&Transform> for SerializableTransform {
 pub struct PlayerSetupResource {
     pub paths: Vec<PathBuf>,
     pub materials: Vec<PathBuf>,
+    pub players: usize,
+    pub physics: PhysicsConfig,
 }
 
 impl Plugin for PlayerPlugin {
@@ -56,17 +90,31 @@ impl Plugin for PlayerPlugin {
         .insert_resource(PlayerSetupResource {
             paths: self.recording_paths.clone(),
             materials: self.materials.clone(),
+            players: self.players,
+            physics: self.physics,
         })
+        .insert_resource(self.control)
         .add_system(kill_system)
         .add_system(record_player_positions)
         .add_system(sync_palyer_lights)
         .add_system(swap_camera)
-        .add_system(player_light_system);
+        .add_system(player_light_system)
+        .add_system_to_stage(CoreStage::PreUpdate, reset_players);
         if self.grpc && self.recording_paths.is_empty() {
             app.add_system(player_input_grpc)
-                .add_system(send_player_view_grpc.before(player_input_grpc));
+                .add_system(apply_surface.after(player_input_grpc))
+                .add_system(clamp_velocity.after(apply_surface))
+                .add_system(
+                    send_player_view_grpc
+                        .before(player_input_grpc)
+                        .after(checkpoint_collection)
+                        .after(compute_rewards)
+                        .after(check_termination),
+                );
         } else if self.recording_paths.is_empty() {
-            app.add_system(player_debug_inputs);
+            app.add_system(player_debug_inputs)
+                .add_system(apply_surface.after(player_debug_inputs))
+                .add_system(clamp_velocity.after(apply_surface));
         } else {
             app.add_system(movement_playback);
         }
@@ -78,27 +126,43 @@ pub fn setup_player(
     asset_server: &AssetServer,
     meshes: &mut Assets<Mesh>,
     materials: &mut Assets<StandardMaterial>,
-    paths: &[PathBuf],
-    player_materials: &[PathBuf],
+    setup: &PlayerSetupResource,
     start_block: (Vec3, f32),
-) -> Vec<Entity> {
-    let recordings = read_recordings(paths);
+) -> Vec<(Entity, String)> {
+    let recordings = read_recordings(&setup.paths);
+    let player_materials = &setup.materials;
     if recordings.is_empty() {
-        vec![spawn_player(
-            commands,
-            meshes,
-            materials,
-            start_block,
-            (
-                Vec::new(),
-                StandardMaterial {
-                    base_color: Color::ANTIQUE_WHITE,
-                    ..Default::default()
-                },
-            ),
-            0,
-            "self".into(),
-        )]
+        // players are placed next to each other, so that they do not spawn inside each other
+        let offset = (setup.players - 1) as f32 / 2.0;
+        (0..setup.players)
+            .map(|i| {
+                let name = if setup.players == 1 {
+                    "self".into()
+                } else {
+                    format!("player_{i}")
+                };
+                let entity = spawn_player(
+                    commands,
+                    meshes,
+                    materials,
+                    (
+                        start_block.0 + Vec3::X * (i as f32 - offset) * 1.5,
+                        start_block.1,
+                    ),
+                    (
+                        Vec::new(),
+                        StandardMaterial {
+                            base_color: Color::ANTIQUE_WHITE,
+                            ..Default::default()
+                        },
+                    ),
+                    i,
+                    name.clone(),
+                    &setup.physics,
+                );
+                (entity, name)
+            })
+            .collect()
     } else {
         let player_materials = if player_materials.len() < recordings.len() {
             let mut m = player_materials
@@ -130,20 +194,23 @@ pub fn setup_player(
             .zip(player_materials.into_iter())
             .enumerate()
             .map(|(i, ((player_name, transforms), player_material))| {
-                spawn_player(
+                let entity = spawn_player(
                     commands,
                     meshes,
                     materials,
                     start_block,
                     (transforms.transforms, player_material),
                     i,
-                    player_name,
-                )
+                    player_name.clone(),
+                    &setup.physics,
+                );
+                (entity, player_name)
             })
             .collect()
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_player(
     commands: &mut Commands,
     meshes: &mut Assets<Mesh>,
@@ -152,6 +219,7 @@ fn spawn_player(
     player_info: (Vec<SerializableTransform>, StandardMaterial),
     index: usize,
     name: String,
+    physics: &PhysicsConfig,
 ) -> Entity {
     let playback_len = player_info.0.len();
     /* Create the bouncing ball. */
@@ -182,6 +250,7 @@ fn spawn_player(
             linvel: Vec3::ZERO,
             angvel: Vec3::ZERO,
         })
+        .insert(Reward::default())
         .insert(PlayerMarker {
             playback_recording: player_info.0,
             playback_position: 0,
@@ -193,7 +262,17 @@ fn spawn_player(
         player_entity
             .insert(RigidBody::Dynamic)
             .insert(Collider::ball(0.5))
-            .insert(Restitution::coefficient(1.0))
+            .insert(ColliderMassProperties::Mass(physics.mass))
+            // multiplied with the neutral friction of the terrain, so that apply_surface is not averaged away
+            .insert(Friction {
+                coefficient: physics.friction,
+                combine_rule: CoefficientCombineRule::Multiply,
+            })
+            .insert(Restitution::coefficient(physics.restitution))
+            .insert(Damping {
+                linear_damping: physics.linear_damping,
+                angular_damping: physics.angular_damping,
+            })
             .insert(ActiveEvents::COLLISION_EVENTS)
             .id()
     } else {
@@ -253,22 +332,78 @@ fn player_debug_inputs(
 fn player_input_grpc(
     runtime: Res<RuntimeResoure>,
     mut next_frame_receiver: ResMut<NextFrameResource>,
-    mut player_query: Query<&mut Velocity, With<PlayerMarker>>,
+    mut state_sender: ResMut<FrameStateSenderResource>,
+    mut reset_events: EventWriter<ResetEvent>,
+    control: Res<Control>,
+    rapier_config: Res<RapierConfiguration>,
+    mut player_query: Query<(&mut Velocity, &mut ExternalForce, &PlayerMarker)>,
 ) {
+    let dt = match rapier_config.timestep_mode {
+        TimestepMode::Fixed { dt, .. } | TimestepMode::Interpolated { dt, .. } => dt,
+        TimestepMode::Variable { max_dt, .. } => max_dt,
+    };
     runtime.0.block_on(async {
-        let force = next_frame_receiver.0.recv().await.unwrap();
-        if force.x != 0.0 || force.z != 0.0 {
-            for mut impulse in player_query.iter_mut() {
-                impulse.linvel = Vec3::new(
-                    force.x.clamp(-10.0, 10.0),
-                    impulse.linvel.y,
-                    force.z.clamp(-10.0, 10.0),
-                );
+        match next_frame_receiver.0.recv().await.unwrap() {
+            NextFrame::Input(inputs) => {
+                for (mut velocity, mut external_force, player) in player_query.iter_mut() {
+                    if let Some(input) = inputs.get(player.index) {
+                        apply_input(&control, *input, dt, &mut velocity, &mut external_force);
+                    }
+                }
+            }
+            NextFrame::Reset { seed, frame_sender } => {
+                state_sender.0 = frame_sender;
+                reset_events.send(ResetEvent { seed });
             }
         }
     });
 }
 
+fn apply_input(
+    control: &Control,
+    input: PlayerInput,
+    dt: f32,
+    velocity: &mut Velocity,
+    external_force: &mut ExternalForce,
+) {
+    let x = input.x.clamp(-10.0, 10.0);
+    let z = input.z.clamp(-10.0, 10.0);
+    match control.mode {
+        ControlMode::Velocity => {
+            if x != 0.0 || z != 0.0 {
+                velocity.linvel = Vec3::new(x, velocity.linvel.y, z);
+            }
+        }
+        ControlMode::Force => external_force.force = Vec3::new(x, 0.0, z),
+        // the rotation axis is perpendicular to the direction the sphere should roll to
+        ControlMode::Torque => external_force.torque = Vec3::new(z, 0.0, -x),
+        ControlMode::Acceleration => {
+            let current = Vec2::new(velocity.linvel.x, velocity.linvel.z);
+            // slopes may still accelerate the sphere beyond the max speed
+            let next = (current + Vec2::new(x, z) * dt)
+                .clamp_length_max(current.length().max(control.max_speed));
+            velocity.linvel = Vec3::new(next.x, velocity.linvel.y, next.y);
+        }
+    }
+}
+
+/// Removes all players of the previous episode, the world spawns the new ones.
+fn reset_players(
+    mut commands: Commands,
+    mut reset_events: EventReader<ResetEvent>,
+    mut positions: ResMut<PlayerMovement>,
+    players: Query<Entity, With<PlayerMarker>>,
+    lights: Query<Entity, With<PlayerLight>>,
+) {
+    if reset_events.iter().last().is_none() {
+        return;
+    }
+    for entity in players.iter().chain(lights.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+    positions.transforms.clear();
+}
+
 fn sync_palyer_lights(
     player_transforms: Query<&Transform, Without<PlayerLight>>,
     mut lights: Query<(&mut Transform, &PlayerLight)>,
@@ -280,57 +415,105 @@ fn sync_palyer_lights(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn send_player_view_grpc(
     runtime: Res<RuntimeResoure>,
     state_sender: Res<FrameStateSenderResource>,
     terrain: Res<Terrain>,
-    player_query: Query<(Entity, &Transform, &Velocity), With<PlayerMarker>>,
+    player_query: Query<(Entity, &Transform, &Velocity, &PlayerMarker, &Reward)>,
     history: Res<HistoryResource>,
+    observations: Res<ObservationResource>,
     checkpoints: Query<(&Checkpoint, &Transform)>,
+    frame_counter: Res<FrameCounter>,
+    rapier_context: Res<RapierContext>,
 ) {
     let history = history.0.lock().unwrap();
-    let next_state = if let Some((player, player_position, velocity)) = player_query.iter().next() {
-        let history = history.get(&player).unwrap();
-        let next_checkpoint_index = history
-            .collected_checkpoints
-            .last()
-            .map(|c| c.0 + 1)
-            .unwrap_or(0);
-        let distance_to_next_checkpint = checkpoints
-            .iter()
-            .find(|c| c.0.number == next_checkpoint_index)
-            .map(|c| c.1.translation.distance(player_position.translation))
-            .unwrap_or(0.0);
-        let next_checkpint = checkpoints
-            .iter()
-            .find(|c| c.0.number == next_checkpoint_index)
-            .map(|c| c.1.translation);
+    let observations = observations.0.lock().unwrap();
+    let mut players: Vec<_> = player_query.iter().collect();
+    players.sort_unstable_by_key(|p| p.3.index);
+    let entities: Vec<Entity> = players.iter().map(|p| p.0).collect();
+    let next_states: Vec<FrameState> = players
+        .into_iter()
+        .map(|(player, player_position, velocity, marker, reward)| {
+            let history = history.get(&player).unwrap();
+            let next_checkpoint_index = history
+                .collected_checkpoints
+                .last()
+                .map(|c| c.0 + 1)
+                .unwrap_or(0);
+            let distance_to_next_checkpint = checkpoints
+                .iter()
+                .find(|c| c.0.number == next_checkpoint_index)
+                .map(|c| c.1.translation.distance(player_position.translation))
+                .unwrap_or(0.0);
+            let next_checkpint = checkpoints
+                .iter()
+                .find(|c| c.0.number == next_checkpoint_index)
+                .map(|c| c.1.translation);
 
-        let surrounding = terrain
-            .get_heights_around(player_position.translation.x, player_position.translation.z)
-            .into_iter()
-            .map(|q| q.map(|q| (q.texture, q.height)))
-            .collect();
+            let observation = observations.get(marker.index).copied().unwrap_or_default();
+            let heading = heading(player_position.translation, velocity.linvel, next_checkpint);
+            let surrounding = terrain.get_heights_around(
+                player_position.translation.x,
+                player_position.translation.z,
+                &observation.window,
+                if observation.window.heading_aligned {
+                    heading
+                } else {
+                    Vec2::Y
+                },
+            );
 
-        Some(FrameState {
-            surrounding,
-            player: player_position.translation,
-            distance: distance_to_next_checkpint,
-            checkpoint: next_checkpint.unwrap_or(Vec3::ZERO),
-            velocity: velocity.linvel,
-            finished: next_checkpint.is_none(),
+            FrameState {
+                surrounding,
+                player: player_position.translation,
+                distance: distance_to_next_checkpint,
+                checkpoint: next_checkpint.unwrap_or(Vec3::ZERO),
+                checkpoint_index: next_checkpoint_index,
+                velocity: velocity.linvel,
+                angular_velocity: velocity.angvel,
+                frame: frame_counter.count,
+                finished: next_checkpint.is_none(),
+                termination: history.dnf,
+                reward: reward.value,
+                lidar: observation
+                    .lidar
+                    .map(|lidar| {
+                        lidar.cast(
+                            &rapier_context,
+                            &terrain,
+                            &entities,
+                            player,
+                            player_position.translation,
+                            heading,
+                        )
+                    })
+                    .unwrap_or_default(),
+            }
         })
-    } else {
-        None
-    };
+        .collect();
     drop(history);
-    if let Some(next_state) = next_state {
+    drop(observations);
+    if !next_states.is_empty() {
         runtime.0.block_on(async {
-            state_sender.0.send(next_state).await.unwrap();
+            // the receiver is dropped when the client resets the episode, the states are stale in that case
+            let _ = state_sender.0.send(next_states).await;
         });
     }
 }
 
+/// Horizontal direction a player is heading to, towards the next checkpoint while it stands still.
+pub fn heading(position: Vec3, velocity: Vec3, next_checkpoint: Option<Vec3>) -> Vec2 {
+    // the sphere rolls, so its rotation says nothing about the direction it is heading to
+    Vec2::new(velocity.x, velocity.z)
+        .try_normalize()
+        .or_else(|| {
+            next_checkpoint
+                .and_then(|c| Vec2::new(c.x - position.x, c.z - position.z).try_normalize())
+        })
+        .unwrap_or(Vec2::Y)
+}
+
 fn record_player_positions(
     mut positions: ResMut<PlayerMovement>,
     player_query: Query<&Transform, With<PlayerMarker>>,
@@ -345,6 +528,8 @@ fn kill_system(
     mut shutdown_receiver: ResMut<ShutdownResource>,
     positions: Res<PlayerMovement>,
     save_path: Res<SavePathReource>,
+    environment: Res<EnvironmentIdResource>,
+    mut exit: EventWriter<AppExit>,
 ) {
     let receievd = shutdown_receiver.0.try_recv().is_ok();
     if keys.just_pressed(KeyCode::Escape) || receievd {
@@ -359,7 +544,11 @@ fn kill_system(
                 .unwrap();
             file.write_all(positions_json.as_bytes()).unwrap();
         };
-        std::process::exit(0);
+        // only the first environment saves its recording, the others must not end the process before it is written
+        if environment.0 == 0 {
+            std::process::exit(0);
+        }
+        exit.send(AppExit);
     }
 }
 
@@ -1,20 +1,37 @@
-use bevy::prelude::{Entity, Vec3};
+use bevy::prelude::{Entity, Vec2, Vec3};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::thread::JoinHandle;
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    pin::Pin,
+    sync::Arc,
+};
 use tokio::{
     runtime::Runtime,
     sync::{
-        mpsc::{Receiver, Sender},
+        mpsc::{unbounded_channel, Receiver, Sender, UnboundedReceiver, UnboundedSender},
         Mutex,
     },
 };
-use tonic::{transport::Server, Request, Response, Status};
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+use tonic::{transport::Server, Request, Response, Status, Streaming};
 
-use crate::world::{checkpoint::History, load_texture::TextureSections};
+use crate::lidar::{Lidar, LidarHit};
+use crate::physics::PhysicsConfig;
+use crate::player::{heading, Control, ControlMode};
+use crate::world::{
+    checkpoint::{History, CHECKPOINT_RADIUS},
+    load_texture::TextureSections,
+    termination::Termination,
+    terrain::{self, ObservationWindow, TerrainSample},
+    Track,
+};
 
 use self::game::main_service_server::MainServiceServer;
 use self::game::{
-    main_service_server::MainService, Empty, InputRequest, PlayerView, Score, Terrain,
+    main_service_server::MainService, BatchStepRequest, BatchStepResult, Config, Empty,
+    EnvironmentRequest, InputRequest, Map, MapRequest, Observation, PlayerView, ResetRequest,
+    Score, Scores, StateRequest, StepResult, Terrain, Vector,
 };
 
 pub mod game {
@@ -26,39 +43,179 @@ pub mod game {
 
 #[derive(Debug)]
 pub struct FrameState {
-    pub surrounding: Vec<Option<(TextureSections, f32)>>,
+    pub surrounding: Vec<Option<TerrainSample>>,
     pub player: Vec3,
     pub distance: f32,
     pub checkpoint: Vec3,
+    pub checkpoint_index: u8,
     pub velocity: Vec3,
+    pub angular_velocity: Vec3,
+    pub frame: usize,
     pub finished: bool,
+    /// set if the race of the player ended without collecting all checkpoints
+    pub termination: Option<Termination>,
+    pub reward: f32,
+    pub lidar: Vec<LidarHit>,
 }
-#[derive(Debug)]
-pub struct NextFrame {
+impl FrameState {
+    /// the race of the player is over
+    fn done(&self) -> bool {
+        self.finished || self.termination.is_some()
+    }
+}
+impl From<FrameState> for PlayerView {
+    fn from(state: FrameState) -> Self {
+        PlayerView {
+            surrounding: state.surrounding.iter().map(to_terrain).collect(),
+            x: state.player.x,
+            y: state.player.y,
+            z: state.player.z,
+            distance: state.distance,
+            finished: state.finished,
+            done: state.done(),
+            termination: to_termination(state.finished, state.termination) as i32,
+            velocity: Some(state.velocity.into()),
+            angular_velocity: Some(state.angular_velocity.into()),
+            checkpoint: Some(state.checkpoint.into()),
+            checkpoint_index: state.checkpoint_index as i32,
+            frame: state.frame as i64,
+            reward: state.reward,
+            lidar: state
+                .lidar
+                .iter()
+                .map(|hit| match hit {
+                    LidarHit::Terrain(distance, kind) => game::LidarHit {
+                        distance: *distance,
+                        kind: *kind as i32,
+                    },
+                    LidarHit::Player(distance) => game::LidarHit {
+                        distance: *distance,
+                        kind: -2,
+                    },
+                    LidarHit::Nothing => game::LidarHit {
+                        distance: -1.0,
+                        kind: -1,
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Samples outside of the world have the kind -1.
+fn to_terrain(sample: &Option<TerrainSample>) -> Terrain {
+    sample
+        .map(|p| Terrain {
+            height: p.height,
+            kind: p.kind as i32,
+            slope: p.slope,
+            normal: Some(p.normal.into()),
+        })
+        .unwrap_or(Terrain {
+            height: 0.0,
+            kind: -1,
+            slope: 0.0,
+            normal: None,
+        })
+}
+
+fn to_termination(finished: bool, termination: Option<Termination>) -> game::Termination {
+    match termination {
+        _ if finished => game::Termination::Finished,
+        Some(Termination::MaxFrames) => game::Termination::MaxFrames,
+        Some(Termination::LeftTerrain) => game::Termination::LeftTerrain,
+        Some(Termination::Fell) => game::Termination::Fell,
+        Some(Termination::Stuck) => game::Termination::Stuck,
+        None => game::Termination::Running,
+    }
+}
+impl From<Vec3> for Vector {
+    fn from(v: Vec3) -> Self {
+        Vector {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+    }
+}
+
+/// Optional parts of the state of a player, configured by the client.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlayerObservation {
+    pub window: ObservationWindow,
+    pub lidar: Option<Lidar>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PlayerInput {
     pub x: f32,
     pub z: f32,
 }
 
+#[derive(Debug)]
+pub enum NextFrame {
+    /// One input per player, indexed by the player id.
+    Input(Vec<PlayerInput>),
+    /// Starts a new episode, all following frame states are sent through the new frame_sender.
+    Reset {
+        seed: Option<u32>,
+        frame_sender: Sender<Vec<FrameState>>,
+    },
+}
+
+/// The server side ends of the channels to one game instance.
+pub struct EnvironmentConnection {
+    pub frame_receiver: Receiver<Vec<FrameState>>,
+    pub next_frame_sender: Sender<NextFrame>,
+    pub shutdown_sender: Sender<()>,
+    pub history: Arc<std::sync::Mutex<HashMap<Entity, History>>>,
+    pub observations: Arc<std::sync::Mutex<Vec<PlayerObservation>>>,
+    pub map: Arc<std::sync::Mutex<Option<(Arc<terrain::Terrain>, Arc<Track>)>>>,
+}
+
+/// Larger grids would make every state several megabytes big.
+const MAX_OBSERVATION_RADIUS: i32 = 256;
+const MAX_LIDAR_RAYS: i32 = 1024;
+
 pub fn start_server(
-    frame_receiver: Receiver<FrameState>,
-    next_frame_sender: Sender<NextFrame>,
-    shutdown_sender: Sender<()>,
-    history: Arc<std::sync::Mutex<HashMap<Entity, History>>>,
+    connections: Vec<EnvironmentConnection>,
     port: i32,
+    players: usize,
+    control: Control,
+    physics: PhysicsConfig,
+    blind: bool,
 ) -> JoinHandle<()> {
     std::thread::spawn(move || {
         let rt = Runtime::new().unwrap();
         rt.block_on(async {
-            next_frame_sender
-                .send(NextFrame { x: 0.0, z: 0.0 })
-                .await
-                .unwrap();
+            let mut environments = Vec::with_capacity(connections.len());
+            for connection in connections {
+                connection
+                    .next_frame_sender
+                    .send(NextFrame::Input(vec![
+                        PlayerInput { x: 0.0, z: 0.0 };
+                        players
+                    ]))
+                    .await
+                    .unwrap();
+                environments.push(Environment {
+                    players: player_receivers(connection.frame_receiver, players)
+                        .into_iter()
+                        .map(|r| Arc::new(Mutex::new(r)))
+                        .collect(),
+                    inputs: Arc::new(InputCollector::new(connection.next_frame_sender, players)),
+                    history: connection.history,
+                    observations: connection.observations,
+                    map: connection.map,
+                    shutdown_sender: connection.shutdown_sender,
+                });
+            }
             let addr = format!("127.0.0.1:{port}").parse().unwrap();
             let game_server = GameServer {
-                frame_receiver: Mutex::new(frame_receiver),
-                next_frame_sender,
-                history,
-                shutdown_sender,
+                environments,
+                control,
+                physics,
+                blind,
             };
             println!("started server");
             let reflection = tonic_reflection::server::Builder::configure()
@@ -78,10 +235,298 @@ pub fn start_server(
 /// Server has receiver for frame states
 /// Server has sender for calculate_next events
 pub struct GameServer {
-    pub frame_receiver: Mutex<Receiver<FrameState>>,
-    pub next_frame_sender: Sender<NextFrame>,
-    pub shutdown_sender: Sender<()>,
+    /// independent game instances, indexed by the environment id
+    pub environments: Vec<Environment>,
+    pub control: Control,
+    pub physics: PhysicsConfig,
+    /// getMap and getTrack are disabled
+    pub blind: bool,
+}
+
+impl GameServer {
+    fn map(&self, id: i32) -> Result<(Arc<terrain::Terrain>, Arc<Track>), Status> {
+        if self.blind {
+            return Err(Status::permission_denied(
+                "the map is disabled for this game (--blind)",
+            ));
+        }
+        self.environment(id)?
+            .map
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| Status::unavailable("the world has not been generated yet"))
+    }
+
+    fn environment(&self, id: i32) -> Result<&Environment, Status> {
+        usize::try_from(id)
+            .ok()
+            .and_then(|id| self.environments.get(id))
+            .ok_or_else(|| {
+                Status::invalid_argument(format!("there is no environment with id {id}"))
+            })
+    }
+}
+
+/// Connection to one game instance with its own world, players and physics.
+#[derive(Clone)]
+pub struct Environment {
+    /// one receiver for the frame states of each player, indexed by the player id
+    pub players: Vec<Arc<Mutex<FrameReceiver>>>,
+    pub inputs: Arc<InputCollector>,
     pub history: Arc<std::sync::Mutex<HashMap<Entity, History>>>,
+    pub observations: Arc<std::sync::Mutex<Vec<PlayerObservation>>>,
+    pub map: Arc<std::sync::Mutex<Option<(Arc<terrain::Terrain>, Arc<Track>)>>>,
+    pub shutdown_sender: Sender<()>,
+}
+
+impl Environment {
+    fn player(&self, id: i32) -> Result<&Arc<Mutex<FrameReceiver>>, Status> {
+        usize::try_from(id)
+            .ok()
+            .and_then(|id| self.players.get(id))
+            .ok_or_else(|| Status::invalid_argument(format!("there is no player with id {id}")))
+    }
+
+    /// Changes the terrain grid and lidar of all states the game builds for the player from now on.
+    fn observe(&self, player: i32, observation: Observation) -> Result<(), Status> {
+        self.player(player)?;
+        let default = ObservationWindow::default();
+        let radius = observation.radius.unwrap_or(default.radius as i32);
+        if !(0..=MAX_OBSERVATION_RADIUS).contains(&radius) {
+            return Err(Status::invalid_argument(format!(
+                "the radius has to be between 0 and {MAX_OBSERVATION_RADIUS}"
+            )));
+        }
+        let spacing = observation.spacing.unwrap_or(default.spacing);
+        if !(spacing > 0.0 && spacing.is_finite()) {
+            return Err(Status::invalid_argument("the spacing has to be positive"));
+        }
+        let lidar = observation.lidar.map(to_lidar).transpose()?;
+        self.observations.lock().unwrap()[player as usize] = PlayerObservation {
+            window: ObservationWindow {
+                radius: radius as usize,
+                spacing,
+                heading_aligned: observation.heading_aligned,
+            },
+            lidar,
+        };
+        Ok(())
+    }
+
+    /// Samples the terrain grid of a state again, for states the game built before the observation of the player changed.
+    fn resample(&self, player: usize, state: &mut FrameState) {
+        let window = self.observations.lock().unwrap()[player].window;
+        let terrain = self.map.lock().unwrap().as_ref().map(|(t, _)| t.clone());
+        if let Some(terrain) = terrain {
+            let forward = if window.heading_aligned {
+                let next_checkpoint = (!state.finished).then_some(state.checkpoint);
+                heading(state.player, state.velocity, next_checkpoint)
+            } else {
+                Vec2::Y
+            };
+            state.surrounding =
+                terrain.get_heights_around(state.player.x, state.player.z, &window, forward);
+        }
+    }
+
+    async fn step(&self, input: InputRequest) -> Result<StepResult, Status> {
+        let mut receiver = self.player(input.player)?.lock().await;
+        let state = receiver
+            .step(
+                input.player as usize,
+                PlayerInput {
+                    x: input.x,
+                    z: input.z,
+                },
+                &self.inputs,
+            )
+            .await?;
+        if state.done() {
+            self.inputs.finish(input.player as usize).await?;
+        }
+        Ok(StepResult {
+            reward: state.reward,
+            done: state.done(),
+            view: Some(state.into()),
+        })
+    }
+}
+
+fn to_lidar(lidar: game::Lidar) -> Result<Lidar, Status> {
+    if !(1..=MAX_LIDAR_RAYS).contains(&lidar.rays) {
+        return Err(Status::invalid_argument(format!(
+            "the number of rays has to be between 1 and {MAX_LIDAR_RAYS}"
+        )));
+    }
+    if !(0.0..=360.0).contains(&lidar.fov) {
+        return Err(Status::invalid_argument(
+            "the field of view has to be between 0 and 360 degrees",
+        ));
+    }
+    if !(lidar.range > 0.0 && lidar.range.is_finite()) {
+        return Err(Status::invalid_argument("the range has to be positive"));
+    }
+    if !(-90.0..=90.0).contains(&lidar.pitch) {
+        return Err(Status::invalid_argument(
+            "the pitch has to be between -90 and 90 degrees",
+        ));
+    }
+    Ok(Lidar {
+        rays: lidar.rays as usize,
+        fov: lidar.fov,
+        range: lidar.range,
+        pitch: lidar.pitch,
+    })
+}
+
+/// Splits the states the game sends for each frame into one receiver per player.
+fn player_receivers(
+    mut frame_receiver: Receiver<Vec<FrameState>>,
+    players: usize,
+) -> Vec<FrameReceiver> {
+    let (senders, receivers): (Vec<UnboundedSender<FrameState>>, Vec<_>) =
+        (0..players).map(|_| unbounded_channel()).unzip();
+    tokio::spawn(async move {
+        while let Some(states) = frame_receiver.recv().await {
+            for (state, sender) in states.into_iter().zip(senders.iter()) {
+                let _ = sender.send(state);
+            }
+        }
+    });
+    receivers.into_iter().map(FrameReceiver::new).collect()
+}
+
+/// Collects the inputs of all players, the next frame is requested once every player submitted an input.
+pub struct InputCollector {
+    inputs: std::sync::Mutex<Vec<Option<PlayerInput>>>,
+    /// players whose race is over, the next frame does not wait for their inputs
+    done: std::sync::Mutex<Vec<bool>>,
+    /// number of frames requested in the current episode, including the one requested at startup
+    requested_frames: AtomicUsize,
+    next_frame_sender: Sender<NextFrame>,
+}
+
+impl InputCollector {
+    fn new(next_frame_sender: Sender<NextFrame>, players: usize) -> Self {
+        Self {
+            inputs: std::sync::Mutex::new(vec![None; players]),
+            done: std::sync::Mutex::new(vec![false; players]),
+            requested_frames: AtomicUsize::new(1),
+            next_frame_sender,
+        }
+    }
+
+    async fn submit(&self, player: usize, input: PlayerInput) -> Result<(), Status> {
+        let complete = {
+            let mut inputs = self.inputs.lock().unwrap();
+            inputs[player] = Some(input);
+            self.take_complete(&mut inputs)
+        };
+        self.send(complete).await
+    }
+
+    /// Stops waiting for the inputs of a player whose race is over, the player stands still from now on.
+    async fn finish(&self, player: usize) -> Result<(), Status> {
+        let complete = {
+            let mut inputs = self.inputs.lock().unwrap();
+            self.done.lock().unwrap()[player] = true;
+            self.take_complete(&mut inputs)
+        };
+        self.send(complete).await
+    }
+
+    /// Takes the inputs of the next frame once every player that is still racing has submitted one.
+    fn take_complete(&self, inputs: &mut [Option<PlayerInput>]) -> Option<Vec<PlayerInput>> {
+        let done = self.done.lock().unwrap();
+        let waiting = inputs
+            .iter()
+            .zip(done.iter())
+            .any(|(i, d)| i.is_none() && !d);
+        if waiting || inputs.iter().all(|i| i.is_none()) {
+            return None;
+        }
+        Some(
+            inputs
+                .iter_mut()
+                .map(|i| i.take().unwrap_or(PlayerInput { x: 0.0, z: 0.0 }))
+                .collect(),
+        )
+    }
+
+    async fn send(&self, complete: Option<Vec<PlayerInput>>) -> Result<(), Status> {
+        if let Some(inputs) = complete {
+            self.next_frame_sender
+                .send(NextFrame::Input(inputs))
+                .await
+                .map_err(|e| Status::unknown(format!("{e:?}")))?;
+            self.requested_frames.fetch_add(1, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
+    async fn reset(
+        &self,
+        seed: Option<u32>,
+        frame_sender: Sender<Vec<FrameState>>,
+    ) -> Result<(), Status> {
+        self.inputs
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .for_each(|i| *i = None);
+        self.done
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .for_each(|d| *d = false);
+        self.requested_frames.store(0, Ordering::SeqCst);
+        self.next_frame_sender
+            .send(NextFrame::Reset { seed, frame_sender })
+            .await
+            .map_err(|e| Status::unknown(format!("{e:?}")))
+    }
+}
+
+/// The game sends one state per frame before it waits for the inputs of that frame.
+/// Counting the received states allows to skip states that were computed before the latest input.
+pub struct FrameReceiver {
+    receiver: UnboundedReceiver<FrameState>,
+    received: usize,
+}
+
+impl FrameReceiver {
+    fn new(receiver: UnboundedReceiver<FrameState>) -> Self {
+        Self {
+            receiver,
+            received: 0,
+        }
+    }
+
+    async fn recv(&mut self) -> Result<FrameState, Status> {
+        let state = self
+            .receiver
+            .recv()
+            .await
+            .ok_or_else(|| Status::not_found("no new game state available"))?;
+        self.received += 1;
+        Ok(state)
+    }
+
+    /// Applies the input and returns the state of the following frame.
+    async fn step(
+        &mut self,
+        player: usize,
+        input: PlayerInput,
+        inputs: &InputCollector,
+    ) -> Result<FrameState, Status> {
+        // The game computes one more state than inputs were requested, skip everything up to that state.
+        while self.received <= inputs.requested_frames.load(Ordering::SeqCst) {
+            self.recv().await?;
+        }
+        inputs.submit(player, input).await?;
+        self.recv().await
+    }
 }
 
 #[tonic::async_trait]
@@ -89,67 +534,246 @@ impl MainService for GameServer {
     async fn health(&self, _r: Request<Empty>) -> Result<Response<Empty>, Status> {
         Ok(Response::new(Empty {}))
     }
-    async fn get_state(&self, _r: Request<Empty>) -> Result<Response<PlayerView>, Status> {
-        let mut receievr = self.frame_receiver.lock().await;
-        if let Some(state) = receievr.recv().await {
-            Ok(Response::new(PlayerView {
-                surrounding: state
-                    .surrounding
-                    .iter()
-                    .map(|p| {
-                        p.map(|p| Terrain {
-                            height: p.1,
-                            kind: p.0 as i32,
-                        })
-                        .unwrap_or(Terrain {
-                            height: 0.0,
-                            kind: -1,
-                        })
-                    })
-                    .collect(),
-                x: state.player.x,
-                y: state.player.y,
-                z: state.player.z,
-                distance: state.distance,
-                finished: state.finished,
-            }))
-        } else {
-            Err(Status::not_found("no new game state available"))
+    async fn get_state(&self, r: Request<StateRequest>) -> Result<Response<PlayerView>, Status> {
+        let request = r.into_inner();
+        let environment = self.environment(request.environment)?;
+        let observed = match request.observation {
+            Some(observation) => {
+                environment.observe(request.player, observation)?;
+                true
+            }
+            None => false,
+        };
+        let mut receievr = environment.player(request.player)?.lock().await;
+        let mut state = receievr.recv().await?;
+        if observed {
+            // the game has built this state in advance with the previous grid
+            environment.resample(request.player as usize, &mut state);
+        }
+        if state.done() {
+            environment.inputs.finish(request.player as usize).await?;
         }
+        Ok(Response::new(state.into()))
     }
     async fn input(&self, r: Request<InputRequest>) -> Result<Response<Empty>, Status> {
         let input = r.into_inner();
-        self.next_frame_sender
-            .send(NextFrame {
-                x: input.x,
-                z: input.z,
+        let environment = self.environment(input.environment)?;
+        environment.player(input.player)?;
+        environment
+            .inputs
+            .submit(
+                input.player as usize,
+                PlayerInput {
+                    x: input.x,
+                    z: input.z,
+                },
+            )
+            .await?;
+        Ok(Response::new(Empty {}))
+    }
+    async fn step(&self, r: Request<InputRequest>) -> Result<Response<StepResult>, Status> {
+        let input = r.into_inner();
+        let result = self.environment(input.environment)?.step(input).await?;
+        Ok(Response::new(result))
+    }
+    async fn batch_step(
+        &self,
+        r: Request<BatchStepRequest>,
+    ) -> Result<Response<BatchStepResult>, Status> {
+        let inputs = r.into_inner().inputs;
+        // nothing is applied unless every input is valid, a player stepped twice would wait for itself
+        let mut players = HashSet::new();
+        for input in &inputs {
+            self.environment(input.environment)?.player(input.player)?;
+            if !players.insert((input.environment, input.player)) {
+                return Err(Status::invalid_argument(format!(
+                    "player {} of environment {} is stepped more than once",
+                    input.player, input.environment
+                )));
+            }
+        }
+        // all steps run concurrently, a frame needs the inputs of all players of an environment
+        let steps = inputs
+            .into_iter()
+            .map(|input| {
+                let environment = self.environment(input.environment)?.clone();
+                Ok(tokio::spawn(async move { environment.step(input).await }))
             })
-            .await
-            .map_err(|e| Status::unknown(format!("{e:?}")))?;
+            .collect::<Result<Vec<_>, Status>>()?;
+        let mut results = Vec::with_capacity(steps.len());
+        for step in steps {
+            results.push(
+                step.await
+                    .map_err(|e| Status::internal(format!("{e:?}")))??,
+            );
+        }
+        Ok(Response::new(BatchStepResult { results }))
+    }
+
+    type sessionStream = Pin<Box<dyn Stream<Item = Result<PlayerView, Status>> + Send>>;
+
+    async fn session(
+        &self,
+        r: Request<Streaming<InputRequest>>,
+    ) -> Result<Response<Self::sessionStream>, Status> {
+        let mut requests = r.into_inner();
+        let environments = self.environments.clone();
+        let (view_sender, view_receiver) = tokio::sync::mpsc::channel(1);
+        tokio::spawn(async move {
+            loop {
+                let view = match requests.message().await {
+                    Ok(Some(input)) => match usize::try_from(input.environment)
+                        .ok()
+                        .and_then(|id| environments.get(id))
+                    {
+                        Some(environment) => environment
+                            .step(input)
+                            .await
+                            .map(|result| result.view.unwrap_or_default()),
+                        None => Err(Status::invalid_argument(format!(
+                            "there is no environment with id {}",
+                            input.environment
+                        ))),
+                    },
+                    Ok(None) => break,
+                    Err(e) => Err(e),
+                };
+                let done = view.as_ref().map(|v| v.done).unwrap_or(true);
+                if view_sender.send(view).await.is_err() || done {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(
+            Box::pin(ReceiverStream::new(view_receiver)) as Self::sessionStream
+        ))
+    }
+
+    async fn reset(&self, r: Request<ResetRequest>) -> Result<Response<Empty>, Status> {
+        let request = r.into_inner();
+        let environment = self.environment(request.environment)?;
+        let mut receivers = Vec::with_capacity(environment.players.len());
+        for player in environment.players.iter() {
+            receivers.push(player.lock().await);
+        }
+        // Replacing the receivers drops all states of the previous episode that have not been retrieved yet.
+        let (frame_sender, frame_receiver) = tokio::sync::mpsc::channel(1);
+        for (receiver, new_receiver) in receivers
+            .iter_mut()
+            .zip(player_receivers(frame_receiver, environment.players.len()))
+        {
+            **receiver = new_receiver;
+        }
+        environment.inputs.reset(request.seed, frame_sender).await?;
         Ok(Response::new(Empty {}))
     }
+    async fn get_config(&self, _r: Request<Empty>) -> Result<Response<Config>, Status> {
+        Ok(Response::new(Config {
+            control_mode: match self.control.mode {
+                ControlMode::Velocity => game::ControlMode::Velocity,
+                ControlMode::Force => game::ControlMode::Force,
+                ControlMode::Torque => game::ControlMode::Torque,
+                ControlMode::Acceleration => game::ControlMode::Acceleration,
+            } as i32,
+            max_speed: self.control.max_speed,
+            physics: Some(game::Physics {
+                timestep: self.physics.timestep,
+                substeps: self.physics.substeps as i32,
+                gravity: self.physics.gravity,
+                mass: self.physics.mass,
+                friction: self.physics.friction,
+                restitution: self.physics.restitution,
+                linear_damping: self.physics.linear_damping,
+                angular_damping: self.physics.angular_damping,
+                max_velocity: self.physics.max_velocity,
+                surfaces: [
+                    TextureSections::Grass,
+                    TextureSections::Grass2,
+                    TextureSections::Gravel,
+                    TextureSections::Rock,
+                    TextureSections::Snow,
+                ]
+                .into_iter()
+                .map(|kind| {
+                    let surface = self.physics.surfaces.get(kind);
+                    game::Surface {
+                        kind: kind as i32,
+                        friction: surface.friction,
+                        rolling_resistance: surface.rolling_resistance,
+                        max_speed: surface.max_speed,
+                    }
+                })
+                .collect(),
+            }),
+        }))
+    }
+
+    async fn get_map(&self, r: Request<MapRequest>) -> Result<Response<Map>, Status> {
+        let request = r.into_inner();
+        let step = request.step.unwrap_or(1);
+        if step < 1 {
+            return Err(Status::invalid_argument("step has to be at least 1"));
+        }
+        let (terrain, _) = self.map(request.environment)?;
+        let (size, spacing, origin, samples) = terrain.get_map(step as usize);
+        Ok(Response::new(Map {
+            size: size as i32,
+            spacing,
+            origin: Some(Vec3::new(origin.x, 0.0, origin.y).into()),
+            samples: samples.iter().map(to_terrain).collect(),
+        }))
+    }
+
+    async fn get_track(
+        &self,
+        r: Request<EnvironmentRequest>,
+    ) -> Result<Response<game::Track>, Status> {
+        let (_, track) = self.map(r.into_inner().environment)?;
+        Ok(Response::new(game::Track {
+            checkpoints: track
+                .checkpoints
+                .iter()
+                .map(|c| game::Checkpoint {
+                    position: Some((*c).into()),
+                    radius: CHECKPOINT_RADIUS,
+                })
+                .collect(),
+        }))
+    }
+
     async fn kill(&self, _r: Request<Empty>) -> Result<Response<Empty>, Status> {
-        let _ = self.shutdown_sender.send(()).await;
+        for environment in self.environments.iter() {
+            let _ = environment.shutdown_sender.send(()).await;
+        }
         Ok(Response::new(Empty {}))
     }
-    async fn get_score(&self, _r: Request<Empty>) -> Result<Response<Score>, Status> {
-        let status = {
-            if let Some((_, history)) = self.history.lock().unwrap().iter().next() {
-                Score {
-                    timings: history
-                        .collected_checkpoints
-                        .iter()
-                        .map(|h| h.1 as i64)
-                        .collect(),
-                    total: history.total,
-                }
-            } else {
-                return Err(Status::unknown(
-                    "there is no player to retrieve a score for",
-                ));
-            }
-        };
-        let _ = self.shutdown_sender.send(()).await;
-        Ok(Response::new(status))
+    async fn get_score(&self, r: Request<EnvironmentRequest>) -> Result<Response<Scores>, Status> {
+        let environment = self.environment(r.into_inner().environment)?;
+        let mut scores: Vec<Score> = environment
+            .history
+            .lock()
+            .unwrap()
+            .values()
+            .map(|history| Score {
+                timings: history
+                    .collected_checkpoints
+                    .iter()
+                    .map(|h| h.1 as i64)
+                    .collect(),
+                total: history.total,
+                name: history.name.clone(),
+                position: history.position.map(|p| p as i32),
+                frames: history.frames as i64,
+                finished: history.finished(),
+                termination: to_termination(history.finished(), history.dnf) as i32,
+            })
+            .collect();
+        if scores.is_empty() {
+            return Err(Status::unknown(
+                "there is no player to retrieve a score for",
+            ));
+        }
+        scores.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Response::new(Scores { scores }))
     }
 }
@@ -11,16 +11,11 @@ use bevy::{
 };
 use image::{imageops::FilterType, ImageBuffer, RgbaImage};
 
+/// The images of all sections are placed next to each other from left to right.
 pub struct Atlas<A> {
     pub material: StandardMaterial,
-    pub to_uv: HashMap<A, UvCoords>,
-}
-
-pub struct UvCoords {
-    pub top: f32,
-    pub bottom: f32,
-    pub left: f32,
-    pub right: f32,
+    /// index of the section of each marker
+    pub sections: HashMap<A, usize>,
 }
 
 pub struct PbrImages {
@@ -38,7 +33,7 @@ where
     let mut normal: RgbaImage = ImageBuffer::new(1024 * images.len() as u32, 1024);
     let mut roughness: RgbaImage = ImageBuffer::new(1024 * images.len() as u32, 1024);
     let mut ambient: RgbaImage = ImageBuffer::new(1024 * images.len() as u32, 1024);
-    let mut uvs = HashMap::with_capacity(images.len());
+    let mut sections = HashMap::with_capacity(images.len());
     for (i, (marker, pbr_image)) in images.iter().enumerate() {
         let current = read_image(&pbr_image.color);
         set_section(&current, &mut color, i as u32 * 1024);
@@ -53,15 +48,7 @@ where
             .unwrap_or_else(white_image);
         set_section(&current, &mut ambient, i as u32 * 1024);
 
-        uvs.insert(
-            *marker,
-            UvCoords {
-                top: 1.0,
-                bottom: 0.0,
-                left: i as f32 / (images.len() as f32),
-                right: (i + 1) as f32 / (images.len() as f32),
-            },
-        );
+        sections.insert(*marker, i);
     }
 
     Atlas {
@@ -72,7 +59,22 @@ where
             occlusion_texture: Some(image_server.add(to_bevy_image(ambient))),
             ..Default::default()
         },
-        to_uv: uvs,
+        sections,
+    }
+}
+
+/// Same section layout as create_texture, but without loading any images. Used when nothing is rendered.
+pub fn create_untextured<A>(markers: &[A]) -> Atlas<A>
+where
+    A: Eq + Hash + Copy + Clone,
+{
+    Atlas {
+        material: StandardMaterial::default(),
+        sections: markers
+            .iter()
+            .enumerate()
+            .map(|(i, marker)| (*marker, i))
+            .collect(),
     }
 }
 
@@ -0,0 +1,133 @@
+use serde::Deserialize;
+
+use super::{
+    load_texture::TextureSections,
+    noise::{NoiseKind, NoiseLayer, WorldNoise},
+};
+
+/// Assigns the ground kinds of the terrain.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BiomeConfig {
+    /// noise field that is independent of the height, its values are between about -amplitude and amplitude
+    pub moisture: NoiseLayer,
+    /// the first matching rule decides the kind, Grass if none matches
+    pub rules: Vec<BiomeRule>,
+}
+
+/// Matches if all given bounds hold, the minimums are inclusive and the maximums exclusive.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BiomeRule {
+    pub kind: TextureSections,
+    pub min_height: Option<f32>,
+    pub max_height: Option<f32>,
+    /// in degrees
+    pub min_slope: Option<f32>,
+    pub max_slope: Option<f32>,
+    pub min_moisture: Option<f32>,
+    pub max_moisture: Option<f32>,
+}
+
+impl BiomeRule {
+    fn new(kind: TextureSections) -> Self {
+        Self {
+            kind,
+            min_height: None,
+            max_height: None,
+            min_slope: None,
+            max_slope: None,
+            min_moisture: None,
+            max_moisture: None,
+        }
+    }
+
+    fn matches(&self, height: f32, slope: f32, moisture: f32) -> bool {
+        within(height, self.min_height, self.max_height)
+            && within(slope, self.min_slope, self.max_slope)
+            && within(moisture, self.min_moisture, self.max_moisture)
+    }
+}
+
+fn within(value: f32, min: Option<f32>, max: Option<f32>) -> bool {
+    min.map(|min| value >= min).unwrap_or(true) && max.map(|max| value < max).unwrap_or(true)
+}
+
+impl Default for BiomeConfig {
+    fn default() -> Self {
+        Self {
+            moisture: NoiseLayer {
+                kind: NoiseKind::Fbm,
+                frequency: 0.005,
+                amplitude: 1.0,
+                octaves: 4,
+                offset: 0.0,
+            },
+            rules: vec![
+                // steep faces are bare rock at every height
+                BiomeRule {
+                    min_slope: Some(40.0),
+                    ..BiomeRule::new(TextureSections::Rock)
+                },
+                // snow only stays on flat ground
+                BiomeRule {
+                    min_height: Some(7.0),
+                    max_slope: Some(25.0),
+                    ..BiomeRule::new(TextureSections::Snow)
+                },
+                BiomeRule {
+                    min_height: Some(5.0),
+                    ..BiomeRule::new(TextureSections::Rock)
+                },
+                BiomeRule {
+                    min_height: Some(0.0),
+                    ..BiomeRule::new(TextureSections::Gravel)
+                },
+                BiomeRule {
+                    max_height: Some(-5.0),
+                    ..BiomeRule::new(TextureSections::Grass)
+                },
+                // wet spots in the hills
+                BiomeRule {
+                    min_moisture: Some(0.5),
+                    ..BiomeRule::new(TextureSections::Grass)
+                },
+                BiomeRule::new(TextureSections::Grass2),
+            ],
+        }
+    }
+}
+
+/// Classifies the size x size heights, spaced scale meters apart, in the same order.
+pub fn classify(
+    heights: &[f32],
+    size: usize,
+    scale: f32,
+    seed: u32,
+    config: &BiomeConfig,
+) -> Vec<TextureSections> {
+    let moisture = WorldNoise::new(seed, std::slice::from_ref(&config.moisture));
+    (0..size)
+        .flat_map(|a| (0..size).map(move |b| (a, b)))
+        .map(|(a, b)| {
+            let height = heights[a * size + b];
+            let slope = slope(heights, size, scale, a, b);
+            let moisture = moisture.get_height(a as f32 * scale, b as f32 * scale);
+            config
+                .rules
+                .iter()
+                .find(|r| r.matches(height, slope, moisture))
+                .map(|r| r.kind)
+                .unwrap_or(TextureSections::Grass)
+        })
+        .collect()
+}
+
+/// Slope in degrees from the height differences to the neighbouring samples.
+fn slope(heights: &[f32], size: usize, scale: f32, a: usize, b: usize) -> f32 {
+    let last = size - 1;
+    let (a0, a1) = (a.saturating_sub(1), (a + 1).min(last));
+    let (b0, b1) = (b.saturating_sub(1), (b + 1).min(last));
+    let da = (heights[a1 * size + b] - heights[a0 * size + b]) / ((a1 - a0).max(1) as f32 * scale);
+    let db = (heights[a * size + b1] - heights[a * size + b0]) / ((b1 - b0).max(1) as f32 * scale);
+    (da * da + db * db).sqrt().atan().to_degrees()
+}
@@ -1,17 +1,17 @@
 use std::collections::HashMap;
 use std::time::Duration;
 
-use bevy::math::Affine2;
 use bevy::prelude::*;
 use bevy::utils::HashSet;
 use bevy_rapier3d::prelude::*;
-use rand::distributions::Standard;
 use rand::prelude::*;
 use rand::rngs::SmallRng;
+use serde::Deserialize;
 
 use crate::player::PlayerMarker;
 use crate::HistoryResource;
 
+use super::termination::Termination;
 use super::terrain::Terrain;
 
 #[derive(Component)]
@@ -23,54 +23,48 @@ pub struct Checkpoint {
     pub remaining_color: Handle<StandardMaterial>,
 }
 pub struct History {
+    pub name: String,
     pub total: i32,
     pub collected_checkpoints: Vec<(u8, usize)>,
+    /// position at the last collected checkpoint, starting at 1
+    pub position: Option<usize>,
+    /// frames until the player finished the track or did not finish, or until now if the player is still racing
+    pub frames: usize,
+    /// set if the race of the player ended without collecting all checkpoints
+    pub dnf: Option<Termination>,
 }
 impl History {
-    fn next(&self) -> u8 {
+    pub fn next(&self) -> u8 {
         self.collected_checkpoints
             .last()
             .map(|l| l.0 + 1)
             .unwrap_or(0)
     }
-    fn finished(&self) -> bool {
+    pub fn finished(&self) -> bool {
         self.collected_checkpoints.len() as i32 == self.total
     }
+    pub fn done(&self) -> bool {
+        self.finished() || self.dnf.is_some()
+    }
 }
 
-pub fn build_checkpoints(
-    materials: &mut Assets<StandardMaterial>,
-    terrain: &mut Terrain,
-    seed: u32,
-) -> Vec<(Vec3, Checkpoint)> {
+/// Generates the track for the given seed, flattens the road into the terrain and returns the checkpoint positions.
+pub fn build_track(terrain: &mut Terrain, seed: u32, config: &TrackConfig) -> Vec<Vec3> {
     let start = {
         let x = 0.0;
         let z = terrain.get_dimensions().1.y / 2.0 - 1.0;
         let y = terrain.get_height(x, z).unwrap() + 1.0;
         Vec3::new(x, y, z)
     };
-    let material = materials.add(StandardMaterial {
-        base_color: Color::rgba(0.0, 0.5, 0.0, 0.5),
-        alpha_mode: AlphaMode::Blend,
-        ..Default::default()
-    });
-    let material_2 = materials.add(StandardMaterial {
-        base_color: Color::rgba(0.5, 0.5, 0.0, 0.5),
-        alpha_mode: AlphaMode::Blend,
-        ..Default::default()
-    });
 
     let mut checkpoints = Vec::new();
-    let track = create_track(Vec2::new(start.x, start.z), seed);
+    let track = create_track(Vec2::new(start.x, start.z), seed, terrain, config);
     let mut track_with_start = vec![Vec2::new(start.x, start.z)];
     track_with_start.extend(track.iter());
     terrain.register_road(&track_with_start);
-    for (i, c) in track_with_start.into_iter().enumerate() {
+    for c in track_with_start.into_iter() {
         if let Some(height) = terrain.get_height(c.x, c.y) {
-            checkpoints.push((
-                Vec3::new(c.x, height + 3.0, c.y),
-                build_checkpoint((i) as u8, material.clone(), material_2.clone()),
-            ));
+            checkpoints.push(Vec3::new(c.x, height + 3.0, c.y));
         } else {
             break;
         }
@@ -78,6 +72,33 @@ pub fn build_checkpoints(
     checkpoints
 }
 
+pub fn build_checkpoints(
+    materials: &mut Assets<StandardMaterial>,
+    track: &[Vec3],
+) -> Vec<(Vec3, Checkpoint)> {
+    let material = materials.add(StandardMaterial {
+        base_color: Color::rgba(0.0, 0.5, 0.0, 0.5),
+        alpha_mode: AlphaMode::Blend,
+        ..Default::default()
+    });
+    let material_2 = materials.add(StandardMaterial {
+        base_color: Color::rgba(0.5, 0.5, 0.0, 0.5),
+        alpha_mode: AlphaMode::Blend,
+        ..Default::default()
+    });
+
+    track
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            (
+                *c,
+                build_checkpoint(i as u8, material.clone(), material_2.clone()),
+            )
+        })
+        .collect()
+}
+
 fn build_checkpoint(
     number: u8,
     material: Handle<StandardMaterial>,
@@ -104,16 +125,26 @@ fn build_checkpoint(
     // });
 }
 
+/// Radius of the checkpoint spheres, a player collects a checkpoint by touching it.
+pub const CHECKPOINT_RADIUS: f32 = 3.0;
+
 #[derive(Resource)]
 pub struct FrameCounter {
     pub count: usize,
 }
+
+/// Sent whenever a player collects its next checkpoint.
+pub struct CheckpointCollected {
+    pub player: Entity,
+}
+
 /// TODO send collection events instead of writing into the history
 /// frame counter per player
 pub fn checkpoint_collection(
     mut commands: Commands,
     history: ResMut<HistoryResource>,
     mut frame_counter: ResMut<FrameCounter>,
+    mut collected: EventWriter<CheckpointCollected>,
     mut collision_events: EventReader<CollisionEvent>,
     mut checkpoints: Query<(Entity, &mut Checkpoint)>,
     mut player_query: Query<(Entity, &mut PlayerMarker)>,
@@ -123,6 +154,9 @@ pub fn checkpoint_collection(
     if !players.is_empty() {
         frame_counter.count += 1;
         let mut history = history.0.lock().unwrap();
+        for h in history.values_mut().filter(|h| !h.done()) {
+            h.frames = frame_counter.count;
+        }
         for e in collision_events.iter() {
             match e {
                 CollisionEvent::Started(e1, e2, _) if players.contains(e1) => {
@@ -133,6 +167,7 @@ pub fn checkpoint_collection(
                         &mut checkpoints,
                         frame_counter.count,
                         &mut player_query,
+                        &mut collected,
                     );
                 }
                 CollisionEvent::Started(e1, e2, _) if players.contains(e2) => {
@@ -143,6 +178,7 @@ pub fn checkpoint_collection(
                         &mut checkpoints,
                         frame_counter.count,
                         &mut player_query,
+                        &mut collected,
                     );
                 }
                 _ => (),
@@ -163,18 +199,23 @@ fn collect_cp(
     checkpoints: &mut Query<(Entity, &mut Checkpoint)>,
     frame_counter: usize,
     player_query: &mut Query<(Entity, &mut PlayerMarker)>,
+    collected: &mut EventWriter<CheckpointCollected>,
 ) {
     if let Ok((_, mut checkpoint)) = checkpoints.get_mut(cp_entity) {
         let number_of_players = histories.len();
         let history = histories.get_mut(&player_entity).unwrap();
-        if checkpoint.number == history.next() {
+        if checkpoint.number == history.next() && history.dnf.is_none() {
             checkpoint.remaining_players.retain(|e| *e != player_entity);
             history
                 .collected_checkpoints
                 .push((checkpoint.number, frame_counter));
+            collected.send(CheckpointCollected {
+                player: player_entity,
+            });
 
             let mut player = player_query.get_mut(player_entity).unwrap().1;
             player.current_position = Some(number_of_players - checkpoint.remaining_players.len());
+            history.position = player.current_position;
 
             if history.finished() {
                 dbg!(
@@ -211,57 +252,206 @@ pub fn only_show_next_checkpoint(
     }
 }
 
-fn create_track(start: Vec2, seed: u32) -> Vec<Vec2> {
-    let generator = TrackGenerator::new(seed as u64, Vec2::new(-1.0, -10.0));
-    generator.generate(start)
+/// Named track configurations for curriculum learning.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackPreset {
+    /// short and smooth tracks without steep segments
+    Easy,
+    /// the default tracks
+    Medium,
+    /// long tracks with sharp turns
+    Hard,
+}
+
+impl TrackPreset {
+    pub fn config(&self) -> TrackConfig {
+        match self {
+            TrackPreset::Easy => TrackConfig {
+                checkpoints: 20,
+                min_segment_length: 12.0,
+                max_segment_length: 15.0,
+                max_turn: 20.0,
+                straight_probability: 0.6,
+                max_gradient: Some(0.15),
+            },
+            TrackPreset::Medium => TrackConfig::default(),
+            TrackPreset::Hard => TrackConfig {
+                checkpoints: 80,
+                min_segment_length: 6.0,
+                max_segment_length: 10.0,
+                max_turn: 90.0,
+                straight_probability: 0.2,
+                max_gradient: None,
+            },
+        }
+    }
+}
+
+/// Parameters of the track generation.
+/// The default config generates the same tracks as the original generator.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TrackConfig {
+    /// number of checkpoints after the start, at most 254; the track ends early at the border of the world
+    pub checkpoints: usize,
+    /// distance between two checkpoints in meters
+    pub min_segment_length: f32,
+    pub max_segment_length: f32,
+    /// maximum change of direction between two segments in degrees
+    pub max_turn: f32,
+    /// probability that the track goes straight after it changed its direction
+    pub straight_probability: f32,
+    /// maximum height difference per meter between two checkpoints, segments are turned to stay below it if possible
+    pub max_gradient: Option<f32>,
+}
+
+impl Default for TrackConfig {
+    fn default() -> Self {
+        Self {
+            checkpoints: 50,
+            min_segment_length: START_DIRECTION.length(),
+            max_segment_length: START_DIRECTION.length(),
+            max_turn: 1.0f32.to_degrees(),
+            straight_probability: 1.0 / 3.0,
+            max_gradient: None,
+        }
+    }
+}
+
+/// First segment of every track, its length is the segment length of the original tracks.
+const START_DIRECTION: Vec2 = Vec2::new(-1.0, -10.0);
+
+fn create_track(start: Vec2, seed: u32, terrain: &Terrain, config: &TrackConfig) -> Vec<Vec2> {
+    let generator = TrackGenerator::new(seed as u64, config);
+    generator.generate(start, terrain)
 }
 
-struct TrackGenerator {
+struct TrackGenerator<'a> {
     rng: SmallRng,
+    config: &'a TrackConfig,
+    /// direction of the next segment, it keeps the length of START_DIRECTION
     current_direction: Vec2,
     state: DirectionState,
     same_direction_count: u32,
+    /// number of equally likely outcomes that go straight and that turn to each side when the direction changes
+    outcomes: (u32, u32),
 }
-impl TrackGenerator {
-    fn new(seed: u64, direction: Vec2) -> Self {
+impl<'a> TrackGenerator<'a> {
+    fn new(seed: u64, config: &'a TrackConfig) -> Self {
         Self {
             rng: SmallRng::seed_from_u64(seed),
-            current_direction: direction,
+            config,
+            current_direction: START_DIRECTION,
             state: DirectionState::Forward,
             same_direction_count: 1,
+            outcomes: direction_outcomes(config.straight_probability),
         }
     }
 
-    fn generate(mut self, start: Vec2) -> Vec<Vec2> {
+    fn generate(mut self, start: Vec2, terrain: &Terrain) -> Vec<Vec2> {
         let mut current = start;
-        (0..50)
+        (0..self.config.checkpoints.min(254))
             .map(|_| {
-                current += self.current_direction;
+                let length = self.segment_length();
+                let segment = self.current_direction * (length / START_DIRECTION.length());
+                current = self.next(current, segment, terrain);
                 self.step();
                 current
             })
             .collect()
     }
 
+    fn segment_length(&mut self) -> f32 {
+        let min = self.config.min_segment_length;
+        let max = self.config.max_segment_length.max(min);
+        if max > min {
+            self.rng.gen_range(min..=max)
+        } else {
+            min
+        }
+    }
+
+    /// Follows the current direction, unless the segment would be steeper than max_gradient.
+    /// Then a few other directions within the max turn are tried and the least steep one is taken.
+    fn next(&mut self, current: Vec2, segment: Vec2, terrain: &Terrain) -> Vec2 {
+        let next = current + segment;
+        let Some(max_gradient) = self.config.max_gradient else {
+            return next;
+        };
+        let length = segment.length();
+        let gradient = |p: Vec2| match (
+            terrain.height_at(current.x, current.y),
+            terrain.height_at(p.x, p.y),
+        ) {
+            (Some(a), Some(b)) => (b - a).abs() / length,
+            // the track is cut off at the border anyway
+            _ => 0.0,
+        };
+        if gradient(next) <= max_gradient {
+            return next;
+        }
+        let max_turn = self.config.max_turn.to_radians();
+        let angles: Vec<f32> = (0..16)
+            .map(|_| {
+                if max_turn > 0.0 {
+                    self.rng.gen_range(-max_turn..=max_turn)
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let rotation = angles
+            .into_iter()
+            .chain([0.0])
+            .map(Mat2::from_angle)
+            .map(|r| (r, gradient(current + r * segment)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(r, _)| r)
+            .unwrap();
+        self.current_direction = rotation * self.current_direction;
+        current + rotation * segment
+    }
+
     fn step(&mut self) {
         let random = self.rng.gen_range(0..10);
         let change = random < self.same_direction_count;
         self.same_direction_count = !change as u32 * self.same_direction_count + 1;
         if change {
-            self.state = self.rng.gen::<DirectionState>();
+            let (straight, turn) = self.outcomes;
+            let outcome = self.rng.gen_range(0..=straight + 2 * turn - 1);
+            self.state = if outcome < straight {
+                DirectionState::Forward
+            } else if outcome < straight + turn {
+                DirectionState::Left
+            } else {
+                DirectionState::Right
+            };
         }
-        self.current_direction = match self.state {
-            DirectionState::Forward => self.current_direction,
-            DirectionState::Left => {
-                let direction = Affine2::from_translation(self.current_direction);
-                (Affine2::from_angle(self.rng.gen_range(0.0..1.0)) * direction).translation
-            }
-            DirectionState::Right => {
-                let direction = Affine2::from_translation(self.current_direction);
-                (Affine2::from_angle(self.rng.gen_range(-1.0..0.0)) * direction).translation
-            }
+        let max_turn = self.config.max_turn.to_radians();
+        if max_turn <= 0.0 {
+            return;
         }
+        let angle = match self.state {
+            DirectionState::Forward => return,
+            DirectionState::Left => self.rng.gen_range(0.0..max_turn),
+            DirectionState::Right => self.rng.gen_range(-max_turn..0.0),
+        };
+        self.current_direction = Mat2::from_angle(angle) * self.current_direction;
+    }
+}
+
+/// Splits a direction change into the fewest equally likely outcomes that go straight with about the given probability
+/// and turn to either side evenly, so that 1/3 gives one outcome each like the original generator.
+fn direction_outcomes(straight_probability: f32) -> (u32, u32) {
+    let p = straight_probability.clamp(0.0, 1.0);
+    if p == 1.0 {
+        return (1, 0);
     }
+    let straight = |turn: u32| (2.0 * turn as f32 * p / (1.0 - p)).round() as u32;
+    (1..=100)
+        .map(|turn| (straight(turn), turn))
+        .find(|&(s, t)| (s as f32 / (s + 2 * t) as f32 - p).abs() < 0.005)
+        .unwrap_or((straight(100), 100))
 }
 
 #[derive(Clone, Copy)]
@@ -271,13 +461,79 @@ enum DirectionState {
     Right,
 }
 
-impl Distribution<DirectionState> for Standard {
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> DirectionState {
-        match rng.gen_range(0..=2) {
-            0 => DirectionState::Forward,
-            1 => DirectionState::Left,
-            2 => DirectionState::Right,
-            _ => unreachable!(),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::WorldConfig;
+    use bevy::math::Affine2;
+
+    /// The generator before the track became configurable.
+    fn original_track(seed: u32, start: Vec2) -> Vec<Vec2> {
+        let mut rng = SmallRng::seed_from_u64(seed as u64);
+        let mut direction = Vec2::new(-1.0, -10.0);
+        let mut state = DirectionState::Forward;
+        let mut same_direction_count = 1;
+        let mut current = start;
+        (0..50)
+            .map(|_| {
+                current += direction;
+                let change = rng.gen_range(0..10) < same_direction_count;
+                same_direction_count = !change as u32 * same_direction_count + 1;
+                if change {
+                    state = match rng.gen_range(0..=2) {
+                        0 => DirectionState::Forward,
+                        1 => DirectionState::Left,
+                        _ => DirectionState::Right,
+                    };
+                }
+                direction = match state {
+                    DirectionState::Forward => direction,
+                    DirectionState::Left => {
+                        let d = Affine2::from_translation(direction);
+                        (Affine2::from_angle(rng.gen_range(0.0..1.0)) * d).translation
+                    }
+                    DirectionState::Right => {
+                        let d = Affine2::from_translation(direction);
+                        (Affine2::from_angle(rng.gen_range(-1.0..0.0)) * d).translation
+                    }
+                };
+                current
+            })
+            .collect()
+    }
+
+    #[test]
+    fn default_config_generates_the_original_tracks() {
+        let terrain = Terrain::new(16, 1.0, 0, &WorldConfig::default());
+        let start = Vec2::new(0.0, 3.0);
+        for seed in 0..20 {
+            assert_eq!(
+                create_track(start, seed, &terrain, &TrackConfig::default()),
+                original_track(seed, start),
+                "seed {seed}"
+            );
         }
     }
+
+    #[test]
+    fn more_checkpoints_extend_the_default_track() {
+        let terrain = Terrain::new(16, 1.0, 0, &WorldConfig::default());
+        let start = Vec2::new(0.0, 3.0);
+        let config = TrackConfig {
+            checkpoints: 51,
+            ..Default::default()
+        };
+        let track = create_track(start, 7, &terrain, &config);
+        assert_eq!(track.len(), 51);
+        assert_eq!(track[..50], original_track(7, start));
+    }
+
+    #[test]
+    fn direction_outcomes_match_the_straight_probability() {
+        assert_eq!(direction_outcomes(1.0 / 3.0), (1, 1));
+        assert_eq!(direction_outcomes(0.6), (3, 1));
+        assert_eq!(direction_outcomes(0.2), (1, 2));
+        assert_eq!(direction_outcomes(0.0), (0, 1));
+        assert_eq!(direction_outcomes(1.0), (1, 0));
+    }
 }
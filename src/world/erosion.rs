@@ -0,0 +1,173 @@
+use std::time::Instant;
+
+use bevy::prelude::*;
+use rand::prelude::*;
+use rand::rngs::SmallRng;
+use serde::Deserialize;
+
+/// Parameters of the erosion that is applied to the generated heights.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ErosionConfig {
+    /// number of simulated water droplets, 0 disables the hydraulic erosion
+    pub droplets: usize,
+    /// maximum number of steps of a droplet
+    pub lifetime: usize,
+    /// how much a droplet keeps its previous direction instead of following the slope, from 0 to 1
+    pub inertia: f32,
+    /// sediment a droplet can carry per slope (meters of descent per meter), speed and water
+    pub capacity: f32,
+    pub min_capacity: f32,
+    /// share of the free capacity that is eroded per step
+    pub erosion: f32,
+    /// share of the excess sediment that is deposited per step
+    pub deposition: f32,
+    /// share of the water that evaporates per step
+    pub evaporation: f32,
+    pub gravity: f32,
+    /// iterations of the thermal erosion, 0 disables it
+    pub thermal_iterations: usize,
+    /// steepest stable slope in degrees, steeper slopes crumble
+    pub talus_angle: f32,
+    /// share of the material above the stable slope that slides down per iteration
+    pub thermal_rate: f32,
+}
+
+impl Default for ErosionConfig {
+    fn default() -> Self {
+        Self {
+            droplets: 50000,
+            lifetime: 30,
+            inertia: 0.05,
+            capacity: 4.0,
+            min_capacity: 0.01,
+            erosion: 0.3,
+            deposition: 0.3,
+            evaporation: 0.01,
+            gravity: 4.0,
+            thermal_iterations: 20,
+            talus_angle: 35.0,
+            thermal_rate: 0.5,
+        }
+    }
+}
+
+/// Erodes the size x size heights in place, spaced scale meters apart. The result only depends on the inputs.
+pub fn erode(heights: &mut [f32], size: usize, scale: f32, seed: u32, config: &ErosionConfig) {
+    let start = Instant::now();
+    hydraulic(heights, size, scale, seed, config);
+    thermal(heights, size, scale, config);
+    info!("erosion took {:?}", start.elapsed());
+}
+
+/// Droplets run downhill from random positions. They pick up sediment while they accelerate and drop it when they slow down or run into a pit.
+/// A droplet moves one sample per step, slopes are measured per meter like the talus angle of the thermal erosion.
+fn hydraulic(heights: &mut [f32], size: usize, scale: f32, seed: u32, config: &ErosionConfig) {
+    if size < 2 {
+        return;
+    }
+    let mut rng = SmallRng::seed_from_u64(seed as u64);
+    let max = (size - 1) as f32;
+    for _ in 0..config.droplets {
+        let mut position = Vec2::new(rng.gen_range(0.0..max), rng.gen_range(0.0..max));
+        let mut direction = Vec2::ZERO;
+        let mut speed = 1.0;
+        let mut water = 1.0;
+        let mut sediment = 0.0;
+        for _ in 0..config.lifetime {
+            let (height, gradient) = height_and_gradient(heights, size, position);
+            let gradient = gradient / scale;
+            direction = (direction * config.inertia - gradient * (1.0 - config.inertia))
+                .normalize_or_zero();
+            let next = position + direction;
+            if direction == Vec2::ZERO
+                || next.x < 0.0
+                || next.y < 0.0
+                || next.x >= max
+                || next.y >= max
+            {
+                break;
+            }
+            let delta = height_and_gradient(heights, size, next).0 - height;
+            let slope = -delta / scale;
+            let capacity = (slope * speed * water * config.capacity).max(config.min_capacity);
+            if delta > 0.0 || sediment > capacity {
+                // fill the pit in front of the droplet, or drop what it can not carry anymore
+                let amount = if delta > 0.0 {
+                    delta.min(sediment)
+                } else {
+                    (sediment - capacity) * config.deposition
+                };
+                sediment -= amount;
+                change_height(heights, size, position, amount);
+            } else {
+                // never dig deeper than the next position
+                let amount = ((capacity - sediment) * config.erosion).min(-delta);
+                sediment += amount;
+                change_height(heights, size, position, -amount);
+            }
+            speed = (speed * speed - delta * config.gravity).max(0.0).sqrt();
+            water *= 1.0 - config.evaporation;
+            position = next;
+        }
+    }
+}
+
+/// Moves material from every sample to its lowest neighbour while the slope between them is steeper than the talus angle.
+fn thermal(heights: &mut [f32], size: usize, scale: f32, config: &ErosionConfig) {
+    let talus = config.talus_angle.to_radians().tan() * scale;
+    for _ in 0..config.thermal_iterations {
+        for a in 0..size {
+            for b in 0..size {
+                let index = a * size + b;
+                let lowest = [
+                    (a.checked_sub(1), Some(b)),
+                    (Some(a + 1).filter(|a| *a < size), Some(b)),
+                    (Some(a), b.checked_sub(1)),
+                    (Some(a), Some(b + 1).filter(|b| *b < size)),
+                ]
+                .into_iter()
+                .filter_map(|(a, b)| Some(a? * size + b?))
+                .min_by(|i, j| heights[*i].total_cmp(&heights[*j]));
+                let Some(lowest) = lowest else {
+                    continue;
+                };
+                let difference = heights[index] - heights[lowest];
+                if difference > talus {
+                    let amount = (difference - talus) / 2.0 * config.thermal_rate;
+                    heights[index] -= amount;
+                    heights[lowest] += amount;
+                }
+            }
+        }
+    }
+}
+
+/// Bilinear height and gradient at a position between the samples, in samples.
+fn height_and_gradient(heights: &[f32], size: usize, position: Vec2) -> (f32, Vec2) {
+    let (a, b) = (position.x as usize, position.y as usize);
+    let (fa, fb) = (position.x.fract(), position.y.fract());
+    let h00 = heights[a * size + b];
+    let h10 = heights[(a + 1) * size + b];
+    let h01 = heights[a * size + b + 1];
+    let h11 = heights[(a + 1) * size + b + 1];
+    let height = h00 * (1.0 - fa) * (1.0 - fb)
+        + h10 * fa * (1.0 - fb)
+        + h01 * (1.0 - fa) * fb
+        + h11 * fa * fb;
+    let gradient = Vec2::new(
+        (h10 - h00) * (1.0 - fb) + (h11 - h01) * fb,
+        (h01 - h00) * (1.0 - fa) + (h11 - h10) * fa,
+    );
+    (height, gradient)
+}
+
+/// Distributes the change over the four samples around the position.
+fn change_height(heights: &mut [f32], size: usize, position: Vec2, amount: f32) {
+    let (a, b) = (position.x as usize, position.y as usize);
+    let (fa, fb) = (position.x.fract(), position.y.fract());
+    heights[a * size + b] += amount * (1.0 - fa) * (1.0 - fb);
+    heights[(a + 1) * size + b] += amount * fa * (1.0 - fb);
+    heights[a * size + b + 1] += amount * (1.0 - fa) * fb;
+    heights[(a + 1) * size + b + 1] += amount * fa * fb;
+}
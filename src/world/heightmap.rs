@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+
+use image::imageops::FilterType;
+use serde::Deserialize;
+
+/// Grayscale image that replaces the noise layers. It is stretched over the whole world, the x axis of the image runs along the x axis of the world and the y axis along the z axis.
+#[derive(Debug, Clone, Deserialize)]
+pub struct HeightmapConfig {
+    /// 8 or 16 bit grayscale png, relative to the working directory
+    pub path: PathBuf,
+    /// height difference between black and white in meters
+    #[serde(default = "default_height_scale")]
+    pub height_scale: f32,
+    /// height of black in meters
+    #[serde(default = "default_offset")]
+    pub offset: f32,
+}
+
+fn default_height_scale() -> f32 {
+    50.0
+}
+
+fn default_offset() -> f32 {
+    -25.0
+}
+
+impl HeightmapConfig {
+    /// Heights of a size x size grid in the order of Terrain::new.
+    pub fn load(&self, size: usize) -> Vec<f32> {
+        let image = image::io::Reader::open(&self.path)
+            .unwrap()
+            .with_guessed_format()
+            .unwrap()
+            .decode()
+            .unwrap()
+            .into_luma16();
+        let image = image::imageops::resize(&image, size as u32, size as u32, FilterType::Triangle);
+        (0..size)
+            .flat_map(|z| (0..size).map(move |x| (x, z)))
+            .map(|(x, z)| {
+                let value = image.get_pixel(x as u32, z as u32).0[0] as f32 / u16::MAX as f32;
+                value * self.height_scale + self.offset
+            })
+            .collect()
+    }
+}
@@ -1,8 +1,10 @@
 use bevy::prelude::*;
+use serde::Deserialize;
 
-use crate::texture::{create_texture, Atlas, PbrImages};
+use crate::texture::{create_texture, create_untextured, Atlas, PbrImages};
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TextureSections {
     Grass,
     Grass2,
@@ -68,3 +70,14 @@ pub fn setup_texture_atlas(images: &mut Assets<Image>) -> Atlas<TextureSections>
         images,
     )
 }
+
+/// Atlas without images for headless mode, the sections are in the same order as in setup_texture_atlas.
+pub fn setup_untextured_atlas() -> Atlas<TextureSections> {
+    create_untextured(&[
+        TextureSections::Grass,
+        TextureSections::Grass2,
+        TextureSections::Gravel,
+        TextureSections::Rock,
+        TextureSections::Snow,
+    ])
+}
@@ -1,111 +1,324 @@
+pub mod biome;
 pub mod checkpoint;
+pub mod erosion;
+pub mod heightmap;
 pub mod load_texture;
-mod noise;
+pub mod noise;
+pub mod reward;
+pub mod termination;
 pub mod terrain;
 
+use std::{collections::HashMap, sync::Arc};
+
 use bevy::{
     prelude::{shape::Icosphere, *},
     render::view::NoFrustumCulling,
 };
 
 use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
 
 use crate::{
     player::{setup_player, PlayerSetupResource},
-    HistoryResource,
+    texture::Atlas,
+    HistoryResource, MapResource,
 };
 
 use self::{
+    biome::BiomeConfig,
     checkpoint::{
-        build_checkpoints, checkpoint_collection, only_show_next_checkpoint, Checkpoint,
-        FrameCounter, History,
+        build_checkpoints, build_track, checkpoint_collection, only_show_next_checkpoint,
+        Checkpoint, CheckpointCollected, FrameCounter, History, TrackConfig, CHECKPOINT_RADIUS,
     },
-    load_texture::setup_texture_atlas,
-    terrain::Terrain,
+    erosion::ErosionConfig,
+    heightmap::HeightmapConfig,
+    load_texture::{setup_texture_atlas, setup_untextured_atlas, TextureSections},
+    noise::{default_layers, NoiseLayer},
+    reward::{compute_rewards, RewardWeights},
+    termination::{check_termination, TerminationRules},
+    terrain::{Terrain, TerrainMaterial},
 };
 
 pub struct WorldPlugin {
     pub seed: u32,
+    /// skips loading the terrain textures, they are not needed if nothing is rendered
+    pub headless: bool,
+    pub reward: RewardWeights,
+    pub termination: TerminationRules,
+    /// edge length of the square world in meters
+    pub size: f32,
+    /// meters between two height samples of the terrain
+    pub scale: f32,
+    pub config: WorldConfig,
+}
+
+/// Description of how the terrain is generated.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct WorldConfig {
+    /// noise layers, whose heights are summed up
+    pub layers: Vec<NoiseLayer>,
+    /// image with the heights, replaces the layers if set
+    pub heightmap: Option<HeightmapConfig>,
+    /// erosion of the generated heights, skipped if not set
+    pub erosion: Option<ErosionConfig>,
+    /// rules for the ground kinds
+    pub biomes: BiomeConfig,
+    pub track: TrackConfig,
+}
+
+impl Default for WorldConfig {
+    fn default() -> Self {
+        Self {
+            layers: default_layers(),
+            heightmap: None,
+            erosion: None,
+            biomes: BiomeConfig::default(),
+            track: TrackConfig::default(),
+        }
+    }
 }
 #[derive(Resource)]
 struct Seed {
     value: u32,
 }
 
+#[derive(Resource)]
+struct WorldSettings {
+    headless: bool,
+    size: f32,
+    scale: f32,
+    config: WorldConfig,
+}
+
+/// Checkpoint positions of the current track, in order of collection.
+#[derive(Resource, Clone)]
+pub struct Track {
+    pub checkpoints: Vec<Vec3>,
+}
+
+/// Assets that are created once at startup and reused whenever the world is rebuilt.
+#[derive(Resource)]
+struct WorldAssets {
+    atlas: Atlas<TextureSections>,
+    terrain_material: Handle<TerrainMaterial>,
+    checkpoint_mesh: Handle<Mesh>,
+}
+
+#[derive(Component)]
+struct TerrainMarker;
+
+/// Starts a new episode. If a seed different from the current one is given, terrain and track are regenerated.
+pub struct ResetEvent {
+    pub seed: Option<u32>,
+}
+
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(FrameCounter { count: 0 })
             .insert_resource(Seed { value: self.seed })
+            .insert_resource(WorldSettings {
+                headless: self.headless,
+                size: self.size,
+                scale: self.scale,
+                config: self.config.clone(),
+            })
+            .insert_resource(self.reward.clone())
+            .insert_resource(self.termination.clone())
+            .add_event::<ResetEvent>()
+            .add_event::<CheckpointCollected>()
             .add_system(checkpoint_collection)
+            .add_system(check_termination.after(checkpoint_collection))
+            .add_system(compute_rewards.after(check_termination))
             .add_system(only_show_next_checkpoint)
+            .add_system_to_stage(CoreStage::PreUpdate, reset_world)
             .add_startup_system(setup_world);
+        if self.headless {
+            // the material is never rendered, only its handle is needed
+            app.add_asset::<TerrainMaterial>();
+        } else {
+            app.add_plugin(MaterialPlugin::<TerrainMaterial>::default());
+        }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn setup_world(
     mut commands: Commands,
     history: Res<HistoryResource>,
     asset_server: Res<AssetServer>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut terrain_materials: ResMut<Assets<TerrainMaterial>>,
     mut images: ResMut<Assets<Image>>,
     player_recordings: Res<PlayerSetupResource>,
     seed: Res<Seed>,
+    settings: Res<WorldSettings>,
+    map: Res<MapResource>,
 ) {
-    let mut history = history.0.lock().unwrap();
-    let atlas = setup_texture_atlas(&mut images);
-    let mut terrain = Terrain::new(430, 1.0, seed.value);
-    let checkpoints = build_checkpoints(&mut materials, &mut terrain, seed.value);
-    let players = setup_player(
+    let atlas = if settings.headless {
+        setup_untextured_atlas()
+    } else {
+        setup_texture_atlas(&mut images)
+    };
+    let world_assets = WorldAssets {
+        terrain_material: terrain_materials.add(TerrainMaterial::new(&atlas)),
+        atlas,
+        checkpoint_mesh: meshes.add(
+            Icosphere {
+                radius: CHECKPOINT_RADIUS,
+                subdivisions: 4,
+            }
+            .into(),
+        ),
+    };
+    let (terrain, track) =
+        generate_world(seed.value, settings.size, settings.scale, &settings.config);
+    spawn_race(
         &mut commands,
+        &mut history.0.lock().unwrap(),
         &asset_server,
         &mut meshes,
         &mut materials,
-        &player_recordings.paths,
-        &player_recordings.materials,
-        (checkpoints[0].0, 2.0),
+        &player_recordings,
+        &world_assets,
+        &track,
     );
-    let checkpoint_mesh = meshes.add(
-        Icosphere {
-            radius: 3.0,
-            subdivisions: 4,
+    spawn_terrain(&mut commands, &mut meshes, &terrain, &world_assets);
+    *map.0.lock().unwrap() = Some((Arc::new(terrain.clone()), Arc::new(track.clone())));
+    commands.insert_resource(terrain);
+    commands.insert_resource(track);
+    commands.insert_resource(world_assets);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn reset_world(
+    mut commands: Commands,
+    mut reset_events: EventReader<ResetEvent>,
+    history: Res<HistoryResource>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    player_recordings: Res<PlayerSetupResource>,
+    world_assets: Res<WorldAssets>,
+    settings: Res<WorldSettings>,
+    map: Res<MapResource>,
+    mut seed: ResMut<Seed>,
+    mut frame_counter: ResMut<FrameCounter>,
+    mut terrain: ResMut<Terrain>,
+    mut track: ResMut<Track>,
+    checkpoints: Query<Entity, With<Checkpoint>>,
+    terrain_entities: Query<Entity, With<TerrainMarker>>,
+) {
+    let Some(reset) = reset_events.iter().last() else {
+        return;
+    };
+    for entity in checkpoints.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if let Some(new_seed) = reset.seed.filter(|s| *s != seed.value) {
+        seed.value = new_seed;
+        for entity in terrain_entities.iter() {
+            commands.entity(entity).despawn_recursive();
         }
-        .into(),
+        let (new_terrain, new_track) =
+            generate_world(new_seed, settings.size, settings.scale, &settings.config);
+        spawn_terrain(&mut commands, &mut meshes, &new_terrain, &world_assets);
+        *map.0.lock().unwrap() = Some((Arc::new(new_terrain.clone()), Arc::new(new_track.clone())));
+        *terrain = new_terrain;
+        *track = new_track;
+    }
+    frame_counter.count = 0;
+    spawn_race(
+        &mut commands,
+        &mut history.0.lock().unwrap(),
+        &asset_server,
+        &mut meshes,
+        &mut materials,
+        &player_recordings,
+        &world_assets,
+        &track,
+    );
+}
+
+/// Terrain and track of a world with an edge length of size meters and a height sample every scale meters.
+pub fn generate_world(seed: u32, size: f32, scale: f32, config: &WorldConfig) -> (Terrain, Track) {
+    let samples = (size / scale).round().max(1.0) as usize;
+    let mut terrain = Terrain::new(samples, scale, seed, config);
+    let checkpoints = build_track(&mut terrain, seed, &config.track);
+    (terrain, Track { checkpoints })
+}
+
+/// Spawns the players at the first checkpoint and all checkpoints of the track. Replaces the content of the history.
+#[allow(clippy::too_many_arguments)]
+fn spawn_race(
+    commands: &mut Commands,
+    history: &mut HashMap<Entity, History>,
+    asset_server: &AssetServer,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    player_recordings: &PlayerSetupResource,
+    world_assets: &WorldAssets,
+    track: &Track,
+) {
+    let checkpoints = build_checkpoints(materials, &track.checkpoints);
+    let players = setup_player(
+        commands,
+        asset_server,
+        meshes,
+        materials,
+        player_recordings,
+        (checkpoints[0].0, 2.0),
     );
 
     *history = players
         .iter()
-        .map(|e| {
+        .map(|(e, name)| {
             (
                 *e,
                 History {
+                    name: name.clone(),
                     total: checkpoints.len() as i32,
                     collected_checkpoints: Vec::with_capacity(255),
+                    position: None,
+                    frames: 0,
+                    dnf: None,
                 },
             )
         })
         .collect();
+    let players: Vec<Entity> = players.into_iter().map(|(e, _)| e).collect();
     for (translation, mut checkpoint) in checkpoints {
         checkpoint.remaining_players = players.clone();
         checkpoint.total_player_count = players.len();
         spawn_checkpoint(
-            &mut commands,
+            commands,
             translation,
             checkpoint,
-            checkpoint_mesh.clone(),
+            world_assets.checkpoint_mesh.clone(),
         );
     }
-    let (mesh, collider) = terrain.to_mesh(&atlas);
-    commands.insert_resource(terrain);
-    let mesh = meshes.add(mesh);
+}
+
+fn spawn_terrain(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    terrain: &Terrain,
+    world_assets: &WorldAssets,
+) {
+    let mesh = meshes.add(terrain.to_mesh(&world_assets.atlas));
+    let collider = terrain.to_collider();
     commands
-        .spawn(PbrBundle {
+        .spawn(MaterialMeshBundle {
             mesh,
-            material: materials.add(atlas.material),
+            material: world_assets.terrain_material.clone(),
             ..Default::default()
         })
         .insert(NoFrustumCulling {})
-        .insert(collider);
+        .insert(collider)
+        // the friction of the ground kinds is applied to the players, see apply_surface
+        .insert(Friction::coefficient(1.0))
+        .insert(TerrainMarker);
 }
 
 fn spawn_checkpoint(
@@ -122,7 +335,7 @@ fn spawn_checkpoint(
             ..Default::default()
         })
         .insert(NoFrustumCulling {})
-        .insert(Collider::ball(3.0))
+        .insert(Collider::ball(CHECKPOINT_RADIUS))
         .insert(Sensor)
         .insert(checkpoint);
 }
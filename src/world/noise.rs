@@ -1,58 +1,115 @@
 use ::noise::{NoiseFn, Simplex};
-use noise::Fbm;
+use noise::{Billow, Fbm, MultiFractal, Perlin, RidgedMulti, Worley};
+use serde::Deserialize;
+
+/// Type of the noise function of a layer. The fractal kinds are built from simplex noise.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoiseKind {
+    Fbm,
+    RidgedMulti,
+    Billow,
+    Perlin,
+    Worley,
+}
+
+/// One layer of the terrain height, the heights of all layers are summed up.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct NoiseLayer {
+    pub kind: NoiseKind,
+    /// features per meter
+    pub frequency: f64,
+    /// the noise is scaled from about -1..1 to -amplitude..amplitude meters
+    pub amplitude: f64,
+    /// only used by the fractal kinds
+    pub octaves: usize,
+    /// added to the height of this layer in meters
+    pub offset: f64,
+}
+
+impl Default for NoiseLayer {
+    fn default() -> Self {
+        Self {
+            kind: NoiseKind::Fbm,
+            frequency: 0.01,
+            amplitude: 1.0,
+            octaves: Fbm::<Simplex>::DEFAULT_OCTAVE_COUNT,
+            offset: 0.0,
+        }
+    }
+}
+
+/// The layers of the original terrain.
+pub fn default_layers() -> Vec<NoiseLayer> {
+    [
+        (50.0, 2.5),
+        (10.0, 1.5),
+        (5.0, 0.5),
+        (75.0, 5.5),
+        (100.0, 20.5),
+    ]
+    .into_iter()
+    .map(|(divisor, amplitude)| NoiseLayer {
+        frequency: 1.0 / divisor,
+        amplitude,
+        ..Default::default()
+    })
+    .collect()
+}
 
 pub struct WorldNoise {
     samplers: Vec<NoiseSampler>,
 }
 impl WorldNoise {
-    pub fn new(seed: u32) -> Self {
+    /// The layer with index n is seeded with seed + n.
+    pub fn new(seed: u32, layers: &[NoiseLayer]) -> Self {
         Self {
-            samplers: vec![
-                NoiseSampler {
-                    simplex: Fbm::new(seed),
-                    divisor: 50.0,
-                    height_multiplier: 2.5,
-                },
-                NoiseSampler {
-                    simplex: Fbm::new(seed + 1),
-                    divisor: 10.0,
-                    height_multiplier: 1.5,
-                },
-                NoiseSampler {
-                    simplex: Fbm::new(seed + 2),
-                    divisor: 5.0,
-                    height_multiplier: 0.5,
-                },
-                NoiseSampler {
-                    simplex: Fbm::new(seed + 3),
-                    divisor: 75.0,
-                    height_multiplier: 5.5,
-                },
-                NoiseSampler {
-                    simplex: Fbm::new(seed + 4),
-                    divisor: 100.0,
-                    height_multiplier: 20.5,
-                },
-            ],
+            samplers: layers
+                .iter()
+                .enumerate()
+                .map(|(n, layer)| NoiseSampler::new(seed.wrapping_add(n as u32), layer))
+                .collect(),
         }
     }
 
-    pub fn get_height(&self, x: usize, z: usize) -> f32 {
+    /// Height at the position (x, z) in meters.
+    pub fn get_height(&self, x: f32, z: f32) -> f32 {
         self.samplers.iter().map(|s| s.sample(x, z)).sum()
     }
 }
 
 struct NoiseSampler {
-    simplex: Fbm<Simplex>,
-    divisor: f64,
-    height_multiplier: f64,
+    noise: Box<dyn NoiseFn<f64, 2>>,
+    frequency: f64,
+    amplitude: f64,
+    offset: f64,
 }
 
 impl NoiseSampler {
-    fn sample(&self, x: usize, z: usize) -> f32 {
+    fn new(seed: u32, layer: &NoiseLayer) -> Self {
+        let noise: Box<dyn NoiseFn<f64, 2>> = match layer.kind {
+            NoiseKind::Fbm => Box::new(Fbm::<Simplex>::new(seed).set_octaves(layer.octaves)),
+            NoiseKind::RidgedMulti => {
+                Box::new(RidgedMulti::<Simplex>::new(seed).set_octaves(layer.octaves))
+            }
+            NoiseKind::Billow => Box::new(Billow::<Simplex>::new(seed).set_octaves(layer.octaves)),
+            NoiseKind::Perlin => Box::new(Perlin::new(seed)),
+            NoiseKind::Worley => Box::new(Worley::new(seed)),
+        };
+        Self {
+            noise,
+            frequency: layer.frequency,
+            amplitude: layer.amplitude,
+            offset: layer.offset,
+        }
+    }
+
+    fn sample(&self, x: f32, z: f32) -> f32 {
         (self
-            .simplex
-            .get([x as f64 / self.divisor, z as f64 / self.divisor])
-            * self.height_multiplier) as f32
+            .noise
+            .get([x as f64 * self.frequency, z as f64 * self.frequency])
+            * self.amplitude
+            + self.offset) as f32
     }
 }
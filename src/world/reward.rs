@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{player::PlayerMarker, HistoryResource};
+
+use super::{
+    checkpoint::{Checkpoint, CheckpointCollected},
+    terrain::Terrain,
+};
+
+/// Weights of the built-in reward. The reward of a frame is
+/// `progress * meters closer to the next checkpoint + checkpoint * collected checkpoints - time - off_road * (1 if not on the road)`.
+#[derive(Resource, Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RewardWeights {
+    pub progress: f32,
+    pub checkpoint: f32,
+    pub time: f32,
+    pub off_road: f32,
+}
+
+impl Default for RewardWeights {
+    fn default() -> Self {
+        Self {
+            progress: 1.0,
+            checkpoint: 10.0,
+            time: 0.01,
+            off_road: 0.1,
+        }
+    }
+}
+
+/// Reward of the player in the current frame.
+#[derive(Component, Default)]
+pub struct Reward {
+    pub value: f32,
+    /// number and distance of the next checkpoint in the previous frame
+    last_checkpoint: Option<(u8, f32)>,
+}
+
+pub fn compute_rewards(
+    weights: Res<RewardWeights>,
+    terrain: Res<Terrain>,
+    history: Res<HistoryResource>,
+    mut collected: EventReader<CheckpointCollected>,
+    checkpoints: Query<(&Checkpoint, &Transform)>,
+    mut players: Query<(Entity, &Transform, &mut Reward), With<PlayerMarker>>,
+) {
+    let collected: Vec<Entity> = collected.iter().map(|c| c.player).collect();
+    let history = history.0.lock().unwrap();
+    for (entity, transform, mut reward) in players.iter_mut() {
+        let collected = collected.iter().filter(|c| **c == entity).count();
+        let Some(history) = history.get(&entity) else {
+            continue;
+        };
+        if history.done() && collected == 0 {
+            reward.value = 0.0;
+            reward.last_checkpoint = None;
+            continue;
+        }
+        let next = history.next();
+        let next_checkpoint = checkpoints
+            .iter()
+            .find(|c| c.0.number == next)
+            .map(|c| (next, c.1.translation.distance(transform.translation)));
+        let progress = match (reward.last_checkpoint, next_checkpoint) {
+            (Some((last, last_distance)), Some((next, distance))) if last == next => {
+                last_distance - distance
+            }
+            _ => 0.0,
+        };
+        let off_road = !terrain
+            .is_road(transform.translation.x, transform.translation.z)
+            .unwrap_or(false);
+        reward.value = weights.progress * progress + weights.checkpoint * collected as f32
+            - weights.time
+            - weights.off_road * off_road as u8 as f32;
+        reward.last_checkpoint = next_checkpoint;
+    }
+}
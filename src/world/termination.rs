@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+
+use crate::{player::PlayerMarker, HistoryResource};
+
+use super::{checkpoint::FrameCounter, terrain::Terrain};
+
+/// Why a player stopped racing without collecting all checkpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    MaxFrames,
+    LeftTerrain,
+    Fell,
+    Stuck,
+}
+
+/// Rules for ending the race of a player early, every rule is disabled by default.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct TerminationRules {
+    pub max_frames: Option<usize>,
+    pub leave_terrain: bool,
+    pub min_height: Option<f32>,
+    /// frames without collecting a checkpoint
+    pub stuck_frames: Option<usize>,
+}
+
+impl TerminationRules {
+    fn check(
+        &self,
+        frame: usize,
+        last_collection: usize,
+        position: Vec3,
+        terrain: &Terrain,
+    ) -> Option<Termination> {
+        let (min, max) = terrain.get_dimensions();
+        if self.leave_terrain
+            && (position.x < min.x
+                || position.z < min.y
+                || position.x >= max.x
+                || position.z >= max.y)
+        {
+            Some(Termination::LeftTerrain)
+        } else if self.min_height.map(|h| position.y < h).unwrap_or(false) {
+            Some(Termination::Fell)
+        } else if self
+            .stuck_frames
+            .map(|n| frame - last_collection >= n)
+            .unwrap_or(false)
+        {
+            Some(Termination::Stuck)
+        } else if self.max_frames.map(|n| frame >= n).unwrap_or(false) {
+            Some(Termination::MaxFrames)
+        } else {
+            None
+        }
+    }
+}
+
+/// Records a DNF in the history of every player that broke one of the rules in this frame.
+pub fn check_termination(
+    rules: Res<TerminationRules>,
+    terrain: Res<Terrain>,
+    history: Res<HistoryResource>,
+    frame_counter: Res<FrameCounter>,
+    players: Query<(Entity, &Transform), With<PlayerMarker>>,
+) {
+    let mut history = history.0.lock().unwrap();
+    for (entity, transform) in players.iter() {
+        let Some(history) = history.get_mut(&entity) else {
+            continue;
+        };
+        if history.done() {
+            continue;
+        }
+        let last_collection = history
+            .collected_checkpoints
+            .last()
+            .map(|c| c.1)
+            .unwrap_or(0);
+        history.dnf = rules.check(
+            frame_counter.count,
+            last_collection,
+            transform.translation,
+            &terrain,
+        );
+    }
+}
@@ -1,36 +1,122 @@
 use crate::{texture::Atlas, world::noise::WorldNoise};
 
-use super::load_texture::TextureSections;
+use super::{biome::classify, erosion::erode, load_texture::TextureSections, WorldConfig};
 use bevy::{
     prelude::*,
-    render::{mesh::Indices, render_resource::PrimitiveTopology},
+    reflect::TypeUuid,
+    render::{
+        mesh::Indices,
+        render_resource::{AsBindGroup, PrimitiveTopology, ShaderRef},
+    },
 };
 use bevy_rapier3d::prelude::Collider;
 use statrs::statistics::Statistics;
 
-#[derive(Resource)]
+/// Shape of the terrain grid that is sent to a player with each state.
+#[derive(Debug, Clone, Copy)]
+pub struct ObservationWindow {
+    /// samples in each direction from the player, the grid has (2 * radius + 1)^2 samples
+    pub radius: usize,
+    /// distance between two neighbouring samples in meters
+    pub spacing: f32,
+    /// rotates the grid, so that its rows run along the movement direction of the player
+    pub heading_aligned: bool,
+}
+impl Default for ObservationWindow {
+    fn default() -> Self {
+        Self {
+            radius: 32,
+            spacing: 1.0,
+            heading_aligned: false,
+        }
+    }
+}
+
+/// Blends the sections of the terrain atlases with the weights in the vertex colors of the mesh, see Terrain::to_mesh.
+/// All atlases have the same layout and use the textures like the StandardMaterial.
+#[derive(AsBindGroup, TypeUuid, Debug, Clone)]
+#[uuid = "f9d73cc2-16d4-4516-95d4-2f9a4aac01fe"]
+pub struct TerrainMaterial {
+    #[texture(0)]
+    #[sampler(1)]
+    pub atlas: Option<Handle<Image>>,
+    /// number of sections in the atlas
+    #[uniform(2)]
+    pub sections: u32,
+    #[texture(3)]
+    pub normal_atlas: Option<Handle<Image>>,
+    #[texture(4)]
+    pub metallic_roughness_atlas: Option<Handle<Image>>,
+    #[texture(5)]
+    pub occlusion_atlas: Option<Handle<Image>>,
+}
+
+impl TerrainMaterial {
+    pub fn new(atlas: &Atlas<TextureSections>) -> Self {
+        Self {
+            atlas: atlas.material.base_color_texture.clone(),
+            sections: atlas.sections.len() as u32,
+            normal_atlas: atlas.material.normal_map_texture.clone(),
+            metallic_roughness_atlas: atlas.material.metallic_roughness_texture.clone(),
+            occlusion_atlas: atlas.material.occlusion_texture.clone(),
+        }
+    }
+}
+
+impl Material for TerrainMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/terrain.wgsl".into()
+    }
+}
+
+#[derive(Resource, Clone)]
 pub struct Terrain {
     quads: Vec<Vec<Quad>>,
+    /// number of quads along each axis
     size: f32,
+    /// meters per quad
+    scale: f32,
 }
 
 impl Terrain {
-    pub fn new(size: usize, s: f32, seed: u32) -> Terrain {
-        let mut min_height = std::f32::INFINITY;
-        let mut max_height = std::f32::NEG_INFINITY;
-        let noise = WorldNoise::new(seed);
-        let quads = (0..size)
-            .map(|x| {
+    /// A terrain of size x size quads, each covering scale x scale meters. The noise is sampled in meters, so the scale only changes the resolution of the landscape and not its shape.
+    pub fn new(size: usize, s: f32, seed: u32, config: &WorldConfig) -> Terrain {
+        // heights[a * size + b] becomes quads[a][b]
+        let mut heights: Vec<f32> = match &config.heightmap {
+            Some(heightmap) => heightmap.load(size),
+            None => {
+                let noise = WorldNoise::new(seed, &config.layers);
                 (0..size)
-                    .map(|z| {
-                        let height = noise.get_height(x, z);
-                        min_height = min_height.min(height);
-                        max_height = max_height.max(height);
-                        Quad {
-                            height,
-                            texture: to_texture(height),
-                            scale: s,
-                        }
+                    .flat_map(|a| (0..size).map(move |b| (a, b)))
+                    .map(|(a, b)| noise.get_height(a as f32 * s, b as f32 * s))
+                    .collect()
+            }
+        };
+        if let Some(erosion) = &config.erosion {
+            erode(&mut heights, size, s, seed, erosion);
+        }
+
+        let min_height = heights.iter().copied().fold(f32::INFINITY, f32::min);
+        let max_height = heights.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        // the moisture gets the first seed after the noise layers
+        let textures = classify(
+            &heights,
+            size,
+            s,
+            seed.wrapping_add(config.layers.len() as u32),
+            &config.biomes,
+        );
+        let quads = heights
+            .chunks(size)
+            .zip(textures.chunks(size))
+            .map(|(heights, textures)| {
+                heights
+                    .iter()
+                    .zip(textures.iter())
+                    .map(|(&height, &texture)| Quad {
+                        height,
+                        texture,
+                        road: false,
                     })
                     .collect::<Vec<_>>()
             })
@@ -40,53 +126,105 @@ impl Terrain {
         Terrain {
             quads,
             size: size as f32,
+            scale: s,
         }
     }
 
-    pub fn to_mesh(&self, atlas: &Atlas<TextureSections>) -> (Mesh, Collider) {
-        let mut positions: Vec<[f32; 3]> =
-            Vec::with_capacity(self.quads.len() * self.quads.len() * 4);
-        let mut normals: Vec<[f32; 3]> =
-            Vec::with_capacity(self.quads.len() * self.quads.len() * 4);
-        let mut uvs: Vec<[f32; 2]> = Vec::with_capacity(self.quads.len() * self.quads.len() * 4);
-        let mut indices = Vec::with_capacity(self.quads.len() * self.quads.len() * 6);
-        let mut current_index = 0;
-
-        for (z, quads) in self.quads.iter().enumerate() {
-            for (x, quad) in quads.iter().enumerate() {
-                let s = self
-                    .surrounding_indices(x, z)
-                    .map(|row| row.map(|index| index.map(|(x1, z1)| self.get(x1, z1).height)));
+    /// Grid mesh with one vertex per quad corner, shared by the up to four quads around it.
+    /// The color of a vertex holds the share of the first four atlas sections in the quads around the corner, the fifth section gets the rest.
+    pub fn to_mesh(&self, atlas: &Atlas<TextureSections>) -> Mesh {
+        let corners = self.quads.len() + 1;
+        let mut positions: Vec<[f32; 3]> = Vec::with_capacity(corners * corners);
+        let mut normals: Vec<[f32; 3]> = Vec::with_capacity(corners * corners);
+        let mut uvs: Vec<[f32; 2]> = Vec::with_capacity(corners * corners);
+        let mut weights: Vec<[f32; 4]> = Vec::with_capacity(corners * corners);
+        for z in 0..corners {
+            for x in 0..corners {
                 let (world_x, world_z) = self.index_to_world(x, z);
-                let (p, n) = quad.to_positions_and_normals(world_x, world_z, &s);
-                positions.extend(p);
-                normals.extend(n);
-                uvs.extend(quad.to_uvs(atlas));
-                indices.extend([
-                    current_index,
-                    current_index + 2,
-                    current_index + 1,
-                    current_index + 2,
-                    current_index + 3,
-                    current_index + 1,
-                ]);
-                current_index += 4;
+                positions.push([world_x, self.corner_height(x, z), world_z]);
+                normals.push(self.corner_normal(x, z).to_array());
+                // every quad shows one full repetition of its texture
+                uvs.push([x as f32, z as f32]);
+                weights.push(self.corner_weights(x, z, atlas));
+            }
+        }
+
+        let mut indices = Vec::with_capacity(self.quads.len() * self.quads.len() * 6);
+        for z in 0..self.quads.len() {
+            for x in 0..self.quads.len() {
+                indices.extend(
+                    cell_triangles(x, z)
+                        .iter()
+                        .flatten()
+                        .map(|(x, z)| (z * corners + x) as u32),
+                );
             }
         }
 
-        let collider = Collider::trimesh(
-            positions
-                .iter()
-                .map(|p| Vec3::new(p[0], p[1], p[2]))
-                .collect(),
-            indices.chunks(3).map(|c| [c[0], c[1], c[2]]).collect(),
-        );
         let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
         mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
         mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
         mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, weights);
         mesh.set_indices(Some(Indices::U32(indices)));
-        (mesh, collider)
+        mesh
+    }
+
+    /// Heightfield through the corners of all quads, the same surface as the mesh.
+    /// Both split each cell along the diagonal from (x, z + 1) to (x + 1, z), see cell_triangles.
+    pub fn to_collider(&self) -> Collider {
+        let corners = self.quads.len() + 1;
+        // rapier expects the heights column by column, columns run along the x axis
+        let heights = (0..corners)
+            .flat_map(|x| (0..corners).map(move |z| (x, z)))
+            .map(|(x, z)| self.corner_height(x, z))
+            .collect();
+        Collider::heightfield(
+            heights,
+            corners,
+            corners,
+            Vec3::new(self.size * self.scale, 1.0, self.size * self.scale),
+        )
+    }
+
+    /// The up to four quads that share the corner at the top left of the quad (x, z).
+    fn corner_quads(&self, x: usize, z: usize) -> impl Iterator<Item = &Quad> {
+        [
+            (x.checked_sub(1), z.checked_sub(1)),
+            (Some(x), z.checked_sub(1)),
+            (x.checked_sub(1), Some(z)),
+            (Some(x), Some(z)),
+        ]
+        .into_iter()
+        .filter_map(move |(x, z)| self.validate((x?, z?)))
+        .map(move |(x, z)| self.get(x, z))
+    }
+
+    fn corner_height(&self, x: usize, z: usize) -> f32 {
+        self.corner_quads(x, z).map(|q| q.height as f64).mean() as f32
+    }
+
+    /// Normal from the height gradient between the neighbouring corners.
+    fn corner_normal(&self, x: usize, z: usize) -> Vec3 {
+        let last = self.quads.len();
+        let (left, right) = (x.saturating_sub(1), (x + 1).min(last));
+        let (back, front) = (z.saturating_sub(1), (z + 1).min(last));
+        let dx = (self.corner_height(right, z) - self.corner_height(left, z))
+            / ((right - left) as f32 * self.scale);
+        let dz = (self.corner_height(x, front) - self.corner_height(x, back))
+            / ((front - back) as f32 * self.scale);
+        Vec3::new(-dx, 1.0, -dz).normalize()
+    }
+
+    fn corner_weights(&self, x: usize, z: usize, atlas: &Atlas<TextureSections>) -> [f32; 4] {
+        let quads: Vec<&Quad> = self.corner_quads(x, z).collect();
+        let mut weights = [0.0; 4];
+        for quad in quads.iter() {
+            if let Some(weight) = weights.get_mut(atlas.sections[&quad.texture]) {
+                *weight += 1.0 / quads.len() as f32;
+            }
+        }
+        weights
     }
 
     pub fn get_height(&self, x: f32, z: f32) -> Option<f32> {
@@ -94,27 +232,106 @@ impl Terrain {
             .map(|(x, z)| self.get(x, z).height)
     }
 
-    pub fn get_heights_around(&self, x: f32, z: f32) -> Vec<Option<Quad>> {
-        let size = 32;
-        if let Some(indices) = self.world_to_index(x, z) {
-            self.surrounding(indices.0, indices.1, size)
-                .into_iter()
-                .flatten()
-                .map(|i| i.map(|i| self.get(i.0, i.1).clone()))
-                .collect()
-        } else {
-            vec![None; ((size * 2 + 1) * (size * 2 + 1)) as usize]
+    pub fn is_road(&self, x: f32, z: f32) -> Option<bool> {
+        self.world_to_index(x, z).map(|(x, z)| self.get(x, z).road)
+    }
+
+    pub fn get_kind(&self, x: f32, z: f32) -> Option<TextureSections> {
+        self.world_to_index(x, z)
+            .map(|(x, z)| self.get(x, z).texture)
+    }
+
+    /// Samples the terrain on a grid around (x, z), row by row. Rows run along `forward`, with `forward == Vec2::Y` the grid is aligned with the world axes.
+    pub fn get_heights_around(
+        &self,
+        x: f32,
+        z: f32,
+        window: &ObservationWindow,
+        forward: Vec2,
+    ) -> Vec<Option<TerrainSample>> {
+        let radius = window.radius as isize;
+        let side = Vec2::new(forward.y, -forward.x);
+        let center = Vec2::new(x, z);
+        (-radius..=radius)
+            .flat_map(|row| {
+                (-radius..=radius).map(move |column| {
+                    center + (forward * row as f32 + side * column as f32) * window.spacing
+                })
+            })
+            .map(|p| self.sample(p.x, p.y))
+            .collect()
+    }
+
+    /// Samples the whole terrain at the center of every step-th quad along each axis, row by row along the z axis.
+    /// Returns the number of samples along each axis, the distance between them and the position of the first sample.
+    pub fn get_map(&self, step: usize) -> (usize, f32, Vec2, Vec<Option<TerrainSample>>) {
+        let size = (self.quads.len() + step - 1) / step;
+        let spacing = step as f32 * self.scale;
+        let origin = self.get_dimensions().0 + Vec2::splat(self.scale / 2.0);
+        let samples = (0..size)
+            .flat_map(|z| (0..size).map(move |x| (x, z)))
+            .map(|(x, z)| origin + Vec2::new(x as f32, z as f32) * spacing)
+            .map(|p| self.sample(p.x, p.y))
+            .collect();
+        (size, spacing, origin, samples)
+    }
+
+    fn sample(&self, x: f32, z: f32) -> Option<TerrainSample> {
+        let normal = self.normal_at(x, z)?;
+        Some(TerrainSample {
+            height: self.height_at(x, z)?,
+            kind: self.get_kind(x, z)?,
+            slope: slope(normal),
+            normal,
+        })
+    }
+
+    /// Height of the surface at (x, z), on the plane of the triangle containing the point like the mesh and the collider.
+    pub fn height_at(&self, x: f32, z: f32) -> Option<f32> {
+        let [a, b, c] = self.triangle_at(x, z)?;
+        let normal = (b - a).cross(c - a);
+        Some(a.y - (normal.x * (x - a.x) + normal.z * (z - a.z)) / normal.y)
+    }
+
+    /// Upwards pointing normal of the triangle containing (x, z).
+    pub fn normal_at(&self, x: f32, z: f32) -> Option<Vec3> {
+        let [a, b, c] = self.triangle_at(x, z)?;
+        Some((b - a).cross(c - a).normalize())
+    }
+
+    /// Angle between the surface at (x, z) and the horizontal plane in degrees.
+    pub fn slope_at(&self, x: f32, z: f32) -> Option<f32> {
+        self.normal_at(x, z).map(slope)
+    }
+
+    /// Corners of the triangle of the mesh that contains (x, z), in world coordinates and in the order of cell_triangles.
+    fn triangle_at(&self, x: f32, z: f32) -> Option<[Vec3; 3]> {
+        let u = x / self.scale + self.size / 2.0;
+        let v = z / self.scale + self.size / 2.0;
+        if u < 0.0 || v < 0.0 {
+            return None;
         }
+        let (i, j) = self.validate((u as usize, v as usize))?;
+        // the first triangle holds the corner (i, j), the second the corner (i + 1, j + 1)
+        let triangle = cell_triangles(i, j)[(u.fract() + v.fract() > 1.0) as usize];
+        Some(triangle.map(|(x, z)| {
+            let (world_x, world_z) = self.index_to_world(x, z);
+            Vec3::new(world_x, self.corner_height(x, z), world_z)
+        }))
     }
 
+    /// Minimum and maximum world coordinates covered by the terrain.
     pub fn get_dimensions(&self) -> (Vec2, Vec2) {
-        (
-            Vec2::new(self.size / -2.0, self.size / -2.0),
-            Vec2::new(self.size / 2.0, self.size / 2.0),
-        )
+        let half = self.size * self.scale / 2.0;
+        (Vec2::new(-half, -half), Vec2::new(half, half))
     }
 
-    fn get(&self, x: usize, z: usize) -> &Quad {
+    /// number of quads along each axis
+    pub fn samples(&self) -> usize {
+        self.quads.len()
+    }
+
+    pub fn get(&self, x: usize, z: usize) -> &Quad {
         self.quads.get(z).and_then(|q| q.get(x)).unwrap()
     }
     fn get_mut(&mut self, x: usize, z: usize) -> &mut Quad {
@@ -122,23 +339,30 @@ impl Terrain {
     }
 
     fn index_to_world(&self, x: usize, z: usize) -> (f32, f32) {
-        (x as f32 - self.size / 2.0, z as f32 - self.size / 2.0)
+        (
+            (x as f32 - self.size / 2.0) * self.scale,
+            (z as f32 - self.size / 2.0) * self.scale,
+        )
     }
 
     fn world_to_index(&self, x: f32, z: f32) -> Option<(usize, usize)> {
-        self.validate((
-            (x + self.size / 2.0) as usize,
-            (z + self.size / 2.0) as usize,
-        ))
+        let x = x / self.scale + self.size / 2.0;
+        let z = z / self.scale + self.size / 2.0;
+        if x < 0.0 || z < 0.0 {
+            return None;
+        }
+        self.validate((x as usize, z as usize))
     }
 
+    /// Flattens the terrain along the track, the road is about 7 meters wide regardless of the scale.
     pub fn register_road(&mut self, points: &[Vec2]) {
+        let radius = (3.0 / self.scale).round().max(1.0) as isize;
         for window in points.windows(2) {
             let start = window[0];
             let end = window[1];
-            for p in between(end, start, self.size / (self.quads.len() as f32)) {
+            for p in between(end, start, self.scale) {
                 if let Some((x, z)) = self.world_to_index(p.x, p.y) {
-                    let surrounding = self.surrounding(x, z, 3);
+                    let surrounding = self.surrounding(x, z, radius);
                     let height = surrounding
                         .iter()
                         .flatten()
@@ -150,6 +374,7 @@ impl Terrain {
                         let mut c = self.get_mut(*x, *z);
                         c.height = height as f32;
                         c.texture = TextureSections::Rock;
+                        c.road = true;
                     }
                 }
             }
@@ -170,26 +395,6 @@ impl Terrain {
             .collect()
     }
 
-    fn surrounding_indices(&self, x: usize, z: usize) -> [[Option<(usize, usize)>; 3]; 3] {
-        [
-            [
-                self.validate((x - 1, z - 1)),
-                self.validate((x, z - 1)),
-                self.validate((x + 1, z - 1)),
-            ],
-            [
-                self.validate((x - 1, z)),
-                self.validate((x, z)),
-                self.validate((x + 1, z)),
-            ],
-            [
-                self.validate((x - 1, z + 1)),
-                self.validate((x, z + 1)),
-                self.validate((x + 1, z + 1)),
-            ],
-        ]
-    }
-
     fn left_right(&self, index: (usize, usize), steps: isize) -> Option<(usize, usize)> {
         self.validate((((index.0 as isize) + steps) as usize, index.1))
     }
@@ -206,6 +411,15 @@ impl Terrain {
     }
 }
 
+/// The two triangles of the cell between the corners (x, z) and (x + 1, z + 1), both facing upwards.
+/// The cell is split along the diagonal from (x, z + 1) to (x + 1, z), like rapier does for heightfields.
+fn cell_triangles(x: usize, z: usize) -> [[(usize, usize); 3]; 2] {
+    [
+        [(x, z), (x, z + 1), (x + 1, z)],
+        [(x, z + 1), (x + 1, z + 1), (x + 1, z)],
+    ]
+}
+
 fn between(start: Vec2, end: Vec2, step: f32) -> Vec<Vec2> {
     let direction = end - start;
     let steps = (direction.length() / step).ceil() as usize;
@@ -213,120 +427,164 @@ fn between(start: Vec2, end: Vec2, step: f32) -> Vec<Vec2> {
     (0..steps).map(|i| start + direction * i as f32).collect()
 }
 
+/// The terrain at one point of the grid around a player.
+#[derive(Debug, Clone, Copy)]
+pub struct TerrainSample {
+    pub height: f32,
+    pub kind: TextureSections,
+    /// in degrees
+    pub slope: f32,
+    pub normal: Vec3,
+}
+
 #[derive(Clone)]
 pub struct Quad {
     pub height: f32,
     pub texture: TextureSections,
-    scale: f32,
-}
-impl Quad {
-    fn to_positions_and_normals(
-        &self,
-        x: f32,
-        z: f32,
-        surrounding: &[[Option<f32>; 3]; 3],
-    ) -> ([[f32; 3]; 4], [[f32; 3]; 4]) {
-        let positions = [
-            [
-                self.scale * (x + 0.0),
-                [
-                    surrounding[0][0],
-                    surrounding[0][1],
-                    surrounding[1][0],
-                    surrounding[1][1],
-                ]
-                .into_iter()
-                .map(|v| v.unwrap_or(self.height) as f64)
-                .mean() as f32,
-                self.scale * (z + 0.0),
-            ],
-            [
-                self.scale * (x + 1.0),
-                [
-                    surrounding[0][1],
-                    surrounding[0][2],
-                    surrounding[1][1],
-                    surrounding[1][2],
-                ]
-                .into_iter()
-                .map(|v| v.unwrap_or(self.height) as f64)
-                .mean() as f32,
-                self.scale * (z + 0.0),
-            ],
-            [
-                self.scale * (x + 0.0),
-                [
-                    surrounding[1][0],
-                    surrounding[1][1],
-                    surrounding[2][0],
-                    surrounding[2][1],
-                ]
-                .into_iter()
-                .map(|v| v.unwrap_or(self.height) as f64)
-                .mean() as f32,
-                self.scale * (z + 1.0),
-            ],
-            [
-                self.scale * (x + 1.0),
-                [
-                    surrounding[1][1],
-                    surrounding[1][2],
-                    surrounding[2][1],
-                    surrounding[2][2],
-                ]
-                .into_iter()
-                .map(|v| v.unwrap_or(self.height) as f64)
-                .mean() as f32,
-                self.scale * (z + 1.0),
-            ],
-        ];
-        let n0 = normal(positions[0], positions[2], positions[1]).to_array();
-        let n1 = ((normal(positions[1], positions[0], positions[2])
-            + normal(positions[1], positions[2], positions[3]))
-            / 2.0)
-            .to_array();
-        let n2 = ((normal(positions[1], positions[0], positions[2])
-            + normal(positions[1], positions[2], positions[3]))
-            / 2.0)
-            .to_array();
-        let n3 = normal(positions[3], positions[1], positions[2]).to_array();
-        let normals = [n0, n1, n2, n3];
-
-        (positions, normals)
-    }
-
-    fn to_uvs(&self, atlas: &Atlas<TextureSections>) -> [[f32; 2]; 4] {
-        let coords = atlas.to_uv.get(&self.texture).unwrap();
-        [
-            [coords.left, coords.bottom],
-            [coords.right, coords.bottom],
-            [coords.left, coords.top],
-            [coords.right, coords.top],
-        ]
-    }
+    /// part of the flattened track
+    pub road: bool,
 }
 
-// normal at a, with a triangle spanned by a_b and a_c
-fn normal(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> Vec3 {
-    let a = Vec3::from_array(a);
-    let b = Vec3::from_array(b);
-    let c = Vec3::from_array(c);
-    let v = b - a;
-    let w = c - a;
-    Vec3::new(
-        v.y * w.z - v.z * w.y,
-        v.z * w.x - v.x * w.z,
-        v.x * w.y - v.y * w.x,
-    )
-    .normalize_or_zero()
+fn slope(normal: Vec3) -> f32 {
+    normal.angle_between(Vec3::Y).to_degrees()
 }
 
-fn to_texture(height: f32) -> TextureSections {
-    match height {
-        x if x < -5.0 => TextureSections::Grass,
-        x if x < 0.0 => TextureSections::Grass2,
-        x if x < 5.0 => TextureSections::Gravel,
-        x if x < 7.0 => TextureSections::Rock,
-        _ => TextureSections::Snow,
+#[cfg(test)]
+mod tests {
+    use bevy::{render::mesh::VertexAttributeValues, utils::HashMap};
+    use bevy_rapier3d::prelude::*;
+
+    use super::*;
+    use crate::world::load_texture::setup_untextured_atlas;
+
+    fn terrain() -> Terrain {
+        Terrain::new(32, 2.0, 3, &WorldConfig::default())
+    }
+
+    fn trimesh(terrain: &Terrain) -> Collider {
+        let mesh = terrain.to_mesh(&setup_untextured_atlas());
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("the terrain mesh has no positions");
+        };
+        let Some(Indices::U32(indices)) = mesh.indices() else {
+            panic!("the terrain mesh has no u32 indices");
+        };
+        Collider::trimesh(
+            positions.iter().map(|p| Vec3::from_array(*p)).collect(),
+            indices.chunks(3).map(|t| [t[0], t[1], t[2]]).collect(),
+        )
+    }
+
+    #[test]
+    fn mesh_has_a_vertex_per_corner_and_two_triangles_per_interior_edge() {
+        let terrain = terrain();
+        let n = terrain.samples();
+        let mesh = terrain.to_mesh(&setup_untextured_atlas());
+        assert_eq!(mesh.count_vertices(), (n + 1) * (n + 1));
+        let Some(Indices::U32(indices)) = mesh.indices() else {
+            panic!("the terrain mesh has no u32 indices");
+        };
+        assert_eq!(indices.len(), 6 * n * n);
+
+        let mut edges: HashMap<(u32, u32), usize> = HashMap::new();
+        for triangle in indices.chunks(3) {
+            for (a, b) in [(0, 1), (1, 2), (2, 0)] {
+                let (a, b) = (triangle[a], triangle[b]);
+                *edges.entry((a.min(b), a.max(b))).or_default() += 1;
+            }
+        }
+        // the border edges run along one of the four sides of the grid
+        let corners = n as u32 + 1;
+        let sides = |i: u32| {
+            let (x, z) = (i % corners, i / corners);
+            [x == 0, z == 0, x == n as u32, z == n as u32]
+        };
+        for ((a, b), count) in edges {
+            let on_border = sides(a).iter().zip(sides(b)).any(|(a, b)| *a && b);
+            if on_border {
+                assert_eq!(count, 1, "border edge {a}-{b}");
+            } else {
+                assert_eq!(count, 2, "interior edge {a}-{b}");
+            }
+        }
+    }
+
+    #[test]
+    fn height_at_matches_a_raycast_on_the_collider() {
+        let terrain = terrain();
+        let collider = terrain.to_collider();
+        for i in 0..400 {
+            // irregular steps, so that the points end up everywhere within the cells
+            let x = (i % 20) as f32 * 3.07 - 30.0;
+            let z = (i / 20) as f32 * 2.93 - 29.0;
+            let toi = collider
+                .cast_ray(
+                    Vec3::ZERO,
+                    Quat::IDENTITY,
+                    Vec3::new(x, 1000.0, z),
+                    -Vec3::Y,
+                    2000.0,
+                    true,
+                )
+                .unwrap();
+            let height = terrain.height_at(x, z).unwrap();
+            assert!(
+                (height - (1000.0 - toi)).abs() < 1e-3,
+                "({x}, {z}): height_at {height}, raycast {}",
+                1000.0 - toi
+            );
+        }
+    }
+
+    /// Drops a sphere at start onto the ground and returns its position after every frame.
+    fn roll(ground: Collider, start: Vec3, frames: usize) -> Vec<Vec3> {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(TransformPlugin)
+            .add_plugin(HierarchyPlugin)
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<Mesh>()
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+            .insert_resource(RapierConfiguration {
+                timestep_mode: TimestepMode::Fixed {
+                    dt: 1.0 / 60.0,
+                    substeps: 1,
+                },
+                ..default()
+            });
+        app.world.spawn((ground, TransformBundle::default()));
+        let sphere = app
+            .world
+            .spawn((
+                RigidBody::Dynamic,
+                Collider::ball(0.5),
+                TransformBundle::from(Transform::from_translation(start)),
+            ))
+            .id();
+        (0..frames)
+            .map(|_| {
+                app.update();
+                app.world.get::<Transform>(sphere).unwrap().translation
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sphere_rolls_the_same_on_heightfield_and_mesh() {
+        let terrain = terrain();
+        for i in -3..=3 {
+            let (x, z) = (i as f32 * 7.0, i as f32 * -4.0);
+            let start = Vec3::new(x, terrain.get_height(x, z).unwrap() + 1.0, z);
+            let on_heightfield = roll(terrain.to_collider(), start, 180);
+            let on_mesh = roll(trimesh(&terrain), start, 180);
+            for (frame, (a, b)) in on_heightfield.iter().zip(on_mesh.iter()).enumerate() {
+                assert!(
+                    a.distance(*b) < 0.1,
+                    "start {start}, frame {frame}: heightfield {a}, mesh {b}"
+                );
+            }
+        }
     }
 }